@@ -1,7 +1,7 @@
 use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
 use std::fs::File;
 use std::time::Duration;
-use wikidump::{config, Parser, Site};
+use wikidump::{config, Parser, Site, TitleFilter};
 
 fn parse_wikipedia(file: &'static str, parse_wiki_text: bool) -> Site {
     let parser = Parser::new()
@@ -11,6 +11,13 @@ fn parse_wikipedia(file: &'static str, parse_wiki_text: bool) -> Site {
     parser.parse_file(file).expect("Failed to parse")
 }
 
+fn parse_filtered(file: &'static str, parser: Parser) -> Site {
+    let parser = parser
+        .process_text(false)
+        .use_config(config::wikipedia::english());
+    parser.parse_file(file).expect("Failed to parse")
+}
+
 fn wikipedia_benchmark(c: &mut Criterion) {
     let mut group = c.benchmark_group("Wikipedia");
 
@@ -28,6 +35,16 @@ fn wikipedia_benchmark(c: &mut Criterion) {
     group.bench_function("enwiki_10k_no_parsing_wiki_text", |b| {
         b.iter(|| parse_wikipedia(black_box("benches/enwiki-10k.xml"), black_box(false)))
     });
+    group.bench_function("enwiki_10k_with_time_budget", |b| {
+        b.iter(|| {
+            Parser::new()
+                .remove_newlines(true)
+                .use_config(config::wikipedia::english())
+                .time_budget(Duration::from_secs(1))
+                .parse_file(black_box("benches/enwiki-10k.xml"))
+                .expect("Failed to parse")
+        })
+    });
 
     group.finish();
 }
@@ -73,10 +90,50 @@ fn simplewiki_benchmark(c: &mut Criterion) {
     group.finish();
 }
 
+fn filtered_wikipedia_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Filtered Wikipedia");
+
+    let file_length = File::open("benches/enwiki-10k.xml")
+        .unwrap()
+        .metadata()
+        .unwrap()
+        .len();
+    group.sample_size(60);
+    group.measurement_time(Duration::new(15, 0));
+    group.throughput(Throughput::Bytes(file_length));
+    group.bench_function("enwiki_10k_all_namespaces", |b| {
+        b.iter(|| {
+            parse_filtered(
+                black_box("benches/enwiki-10k.xml"),
+                Parser::new().exclude_pages(false),
+            )
+        })
+    });
+    group.bench_function("enwiki_10k_main_namespace", |b| {
+        b.iter(|| {
+            parse_filtered(
+                black_box("benches/enwiki-10k.xml"),
+                Parser::new().exclude_pages(true),
+            )
+        })
+    });
+    group.bench_function("enwiki_10k_one_title", |b| {
+        b.iter(|| {
+            parse_filtered(
+                black_box("benches/enwiki-10k.xml"),
+                Parser::new().filter_titles(TitleFilter::exact(["Bernard Fisher"])),
+            )
+        })
+    });
+
+    group.finish();
+}
+
 criterion_group!(
     benches,
     wikipedia_benchmark,
     compressed_wikipedia_benchmark,
-    simplewiki_benchmark
+    simplewiki_benchmark,
+    filtered_wikipedia_benchmark
 );
 criterion_main!(benches);
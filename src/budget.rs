@@ -0,0 +1,42 @@
+use std::time::Duration;
+
+/// What the parser does with a revision whose wiki text is over the limits
+/// set with [max_text_size](struct.Parser.html#method.max_text_size) or
+/// [time_budget](struct.Parser.html#method.time_budget).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BudgetAction {
+    /// Leave the text of the revision empty.
+    Skip,
+    /// Use the raw wiki text as the text of the revision, as if processing
+    /// wiki text was disabled.
+    KeepRaw,
+}
+
+/// The reason why the wiki text of a revision was not processed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BudgetExceeded {
+    /// The raw wiki text was larger than the maximum size, so it was not
+    /// parsed at all.
+    TextSize {
+        /// The size of the raw wiki text in bytes.
+        bytes: usize,
+    },
+    /// Processing the wiki text was not done within the time budget.
+    Time {
+        /// How long the parser waited before giving up on the wiki text.
+        elapsed: Duration,
+    },
+}
+
+/// A revision which was over the processing limits of the parser.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OverBudgetRevision {
+    /// The title of the page.
+    pub title: String,
+    /// The ID of the revision.
+    pub revision_id: u64,
+    /// Which limit the revision was over.
+    pub reason: BudgetExceeded,
+}
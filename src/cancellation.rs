@@ -0,0 +1,44 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// Lets a parse be cancelled from elsewhere, e.g., from another thread when
+/// the request it is serving has been cancelled. Clones of a token share the
+/// same state, so cancelling any of them cancels them all.
+///
+/// Once cancelled, the parser stops at the next page or revision, and
+/// returns an [Error::Cancelled](enum.Error.html#variant.Cancelled).
+///
+/// See [Parser::cancellation_token](struct.Parser.html#method.cancellation_token).
+///
+/// # Example
+/// ```rust
+/// use wikidump::{CancellationToken, Error, Parser};
+///
+/// let token = CancellationToken::new();
+/// let parser = Parser::new().cancellation_token(token.clone());
+///
+/// token.cancel();
+/// let err = parser.parse_file("tests/simplewiki.xml").unwrap_err();
+/// assert!(matches!(err.downcast_ref(), Some(Error::Cancelled)));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    /// Creates a token which has not been cancelled.
+    pub fn new() -> CancellationToken {
+        CancellationToken::default()
+    }
+
+    /// Cancels every parse which uses this token or one of its clones.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Returns true if the token has been cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
@@ -0,0 +1,123 @@
+use bzip2::bufread::BzDecoder;
+use std::collections::VecDeque;
+use std::io::{self, BufRead, Read};
+use std::sync::{Arc, Mutex};
+
+/// A point in a dump from which a [PageStream](struct.PageStream.html) can
+/// carry on reading, so that a long running job which was stopped does not
+/// need to start over from the beginning.
+///
+/// Checkpoints are taken with
+/// [PageStream::checkpoint](struct.PageStream.html#method.checkpoint) and
+/// passed to [Parser::resume_from](struct.Parser.html#method.resume_from).
+/// With the `serde` feature, they can be saved alongside the output of the
+/// job.
+///
+/// Resuming a compressed dump seeks to the start of the bzip2 stream which
+/// holds the checkpoint. This is cheap for multistream dumps, like the
+/// `pages-articles-multistream.xml.bz2` dumps of Wikipedia, which start a
+/// new stream every hundred pages. Dumps with a single stream are
+/// decompressed from the beginning again.
+///
+/// # Example
+/// ```rust
+/// use wikidump::Parser;
+///
+/// let parts = [
+///     "tests/parts/simplewiki-pages-articles1.xml-p1p6",
+///     "tests/parts/simplewiki-pages-articles2.xml-p8p12.bz2",
+/// ];
+///
+/// let parser = Parser::new();
+/// let mut stream = parser.stream_files(parts).unwrap();
+/// let first = stream.next().unwrap().unwrap();
+/// let checkpoint = stream.checkpoint().unwrap().clone();
+/// assert_eq!(checkpoint.page_id, first.id);
+///
+/// let parser = Parser::new().resume_from(checkpoint);
+/// let rest = parser.stream_files(parts).unwrap();
+/// assert_eq!(rest.count(), stream.count());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Checkpoint {
+    /// The index of the part file which the last page was read from, or 0
+    /// for a dump which is not split.
+    pub part: usize,
+    /// The ID of the last page which was read.
+    pub page_id: u64,
+    /// The byte offset in the (uncompressed) part file right after the last
+    /// page which was read.
+    pub position: u64,
+    /// The byte offset in the part file of the bzip2 stream which holds
+    /// `position`, which is where reading resumes. This is the same as
+    /// `position` for uncompressed dumps.
+    pub stream_offset: u64,
+    /// The byte offset in the uncompressed part file where the bzip2 stream
+    /// starts.
+    pub stream_position: u64,
+}
+
+/// Where a bzip2 stream starts, both in the compressed file and in the
+/// uncompressed dump.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct StreamStart {
+    pub(crate) offset: u64,
+    pub(crate) position: u64,
+}
+
+/// The starts of the streams which have been decompressed, oldest first.
+pub(crate) type StreamStarts = Arc<Mutex<VecDeque<StreamStart>>>;
+
+/// Decompresses every stream of a multistream bzip2 file, like
+/// `MultiBzDecoder`, while keeping track of where each stream starts.
+pub(crate) struct MultiStreamDecoder<R> {
+    /// The decoder of the current stream, which is only missing while the
+    /// next stream is being started.
+    decoder: Option<BzDecoder<R>>,
+    start: StreamStart,
+    starts: StreamStarts,
+}
+
+impl<R: BufRead> MultiStreamDecoder<R> {
+    /// Creates a decoder which reads from the start of a stream, which is at
+    /// `start` in the file.
+    pub(crate) fn new(reader: R, start: StreamStart) -> MultiStreamDecoder<R> {
+        MultiStreamDecoder {
+            decoder: Some(BzDecoder::new(reader)),
+            start,
+            starts: Arc::new(Mutex::new(VecDeque::from([start]))),
+        }
+    }
+
+    /// Returns a handle to the starts of the streams decompressed so far.
+    pub(crate) fn starts(&self) -> StreamStarts {
+        Arc::clone(&self.starts)
+    }
+}
+
+impl<R: BufRead> Read for MultiStreamDecoder<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            let decoder = self.decoder.as_mut().expect("decoder is always present");
+            let read = decoder.read(buf)?;
+
+            if read > 0 || buf.is_empty() || decoder.get_mut().fill_buf()?.is_empty() {
+                return Ok(read);
+            }
+
+            // The stream has ended and more data follows, so start the next
+            // stream where this one ended.
+            let decoder = self.decoder.take().expect("decoder is always present");
+            self.start = StreamStart {
+                offset: self.start.offset + decoder.total_in(),
+                position: self.start.position + decoder.total_out(),
+            };
+            self.starts
+                .lock()
+                .expect("stream starts are never poisoned")
+                .push_back(self.start);
+            self.decoder = Some(BzDecoder::new(decoder.into_inner()));
+        }
+    }
+}
@@ -0,0 +1,218 @@
+use crate::{Page, PageRevision, TextContent};
+use similar::{capture_diff_slices, Algorithm, DiffTag};
+use std::collections::HashMap;
+
+/// Which text of the revisions is compared by a diff.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffText {
+    /// The raw wiki text, i.e.,
+    /// [PageRevision::raw](struct.PageRevision.html#structfield.raw).
+    Raw,
+    /// The text after processing the wiki text, i.e.,
+    /// [PageRevision::text](struct.PageRevision.html#structfield.text).
+    Processed,
+}
+
+/// The units in which text is compared by a diff.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffUnit {
+    /// Whole lines, including their line break.
+    Lines,
+    /// Words, and the whitespace between them.
+    Words,
+}
+
+/// A range of text which was added or removed by a revision.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DiffRange {
+    /// The byte offset in the text where the range starts.
+    pub start: usize,
+    /// The byte offset in the text where the range ends.
+    pub end: usize,
+    /// The text of the range.
+    pub text: String,
+}
+
+/// The changes a revision made to the text of its parent revision, which is
+/// the revision it was based on.
+///
+/// # Example
+/// ```rust
+/// use wikidump::{DiffText, DiffUnit, Parser};
+///
+/// let parser = Parser::new().exclude_pages(false);
+/// let site = parser.parse_file("tests/simplewiki-history.xml").unwrap();
+///
+/// for diff in site.pages[0].diffs(DiffText::Raw, DiffUnit::Lines) {
+///     for added in &diff.added {
+///         println!("{} added {:?}", diff.revision_id, added.text);
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RevisionDiff {
+    /// The ID of the revision.
+    pub revision_id: u64,
+    /// The ID of the parent revision, or `None` if the revision has no
+    /// parent, in which case all of its text counts as added.
+    pub parent_id: Option<u64>,
+    /// The ranges of the revision's text which are not in its parent, in
+    /// order.
+    pub added: Vec<DiffRange>,
+    /// The ranges of the parent's text which are not in the revision, in
+    /// order.
+    pub removed: Vec<DiffRange>,
+}
+
+impl RevisionDiff {
+    /// Compares the text of a revision to the text of its parent, or to no
+    /// text at all if there is no parent.
+    pub fn between(
+        parent: Option<&PageRevision>,
+        revision: &PageRevision,
+        text: DiffText,
+        unit: DiffUnit,
+    ) -> RevisionDiff {
+        let old = parent.map_or("", |p| text_of(p, text));
+        let new = text_of(revision, text);
+
+        let old_tokens = tokenize(old, unit);
+        let new_tokens = tokenize(new, unit);
+        let old_starts = token_starts(&old_tokens);
+        let new_starts = token_starts(&new_tokens);
+
+        let mut diff = RevisionDiff {
+            revision_id: revision.id,
+            parent_id: parent.map(|p| p.id),
+            added: vec![],
+            removed: vec![],
+        };
+
+        for op in capture_diff_slices(Algorithm::Myers, &old_tokens, &new_tokens) {
+            let (tag, old_range, new_range) = op.as_tag_tuple();
+
+            if tag == DiffTag::Equal {
+                continue;
+            }
+
+            if !old_range.is_empty() {
+                let range = (old_starts[old_range.start], old_starts[old_range.end]);
+                diff.removed.push(diff_range(old, range));
+            }
+
+            if !new_range.is_empty() {
+                let range = (new_starts[new_range.start], new_starts[new_range.end]);
+                diff.added.push(diff_range(new, range));
+            }
+        }
+
+        diff
+    }
+}
+
+impl Page {
+    /// Returns the revision which a revision was based on, matched by its
+    /// [parent_id](struct.PageRevision.html#structfield.parent_id), if it is
+    /// one of the revisions of this page.
+    pub fn parent_of(&self, revision: &PageRevision) -> Option<&PageRevision> {
+        let parent_id = revision.parent_id?;
+
+        self.revisions.iter().find(|r| r.id == parent_id)
+    }
+
+    /// Compares each revision of the page to its parent, in the order of the
+    /// revisions. This is meant for history dumps, which include every
+    /// revision of each page.
+    ///
+    /// Revisions whose parent is not one of the revisions of this page, e.g.,
+    /// because it was not selected by the parser, are left out. So are
+    /// revisions which do not include their text, or whose parent does not,
+    /// like revisions whose text has been deleted.
+    pub fn diffs(&self, text: DiffText, unit: DiffUnit) -> Vec<RevisionDiff> {
+        let by_id = self
+            .revisions
+            .iter()
+            .map(|r| (r.id, r))
+            .collect::<HashMap<_, _>>();
+
+        self.revisions
+            .iter()
+            .filter_map(|revision| {
+                let parent = match revision.parent_id {
+                    Some(id) => Some(*by_id.get(&id)?),
+                    None => None,
+                };
+
+                let included = |r: &PageRevision| r.text_content == TextContent::Included;
+
+                if !included(revision) || parent.is_some_and(|p| !included(p)) {
+                    return None;
+                }
+
+                Some(RevisionDiff::between(parent, revision, text, unit))
+            })
+            .collect()
+    }
+}
+
+fn text_of(revision: &PageRevision, text: DiffText) -> &str {
+    match text {
+        DiffText::Raw => &revision.raw,
+        DiffText::Processed => &revision.text,
+    }
+}
+
+/// Splits text into the tokens which are compared, which together make up
+/// the whole text.
+fn tokenize(text: &str, unit: DiffUnit) -> Vec<&str> {
+    match unit {
+        DiffUnit::Lines => text.split_inclusive('\n').collect(),
+        DiffUnit::Words => {
+            let mut tokens = vec![];
+            let mut start = 0;
+            let mut in_whitespace = None;
+
+            for (i, c) in text.char_indices() {
+                let whitespace = c.is_whitespace();
+
+                if in_whitespace.is_some_and(|w| w != whitespace) {
+                    tokens.push(&text[start..i]);
+                    start = i;
+                }
+
+                in_whitespace = Some(whitespace);
+            }
+
+            if start < text.len() {
+                tokens.push(&text[start..]);
+            }
+
+            tokens
+        }
+    }
+}
+
+/// Returns the byte offset where each token starts, followed by the length
+/// of the whole text.
+fn token_starts(tokens: &[&str]) -> Vec<usize> {
+    let mut starts = Vec::with_capacity(tokens.len() + 1);
+    let mut offset = 0;
+    starts.push(offset);
+
+    for token in tokens {
+        offset += token.len();
+        starts.push(offset);
+    }
+
+    starts
+}
+
+fn diff_range(text: &str, (start, end): (usize, usize)) -> DiffRange {
+    DiffRange {
+        start,
+        end,
+        text: text[start..end].to_string(),
+    }
+}
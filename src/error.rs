@@ -0,0 +1,64 @@
+use std::fmt;
+use std::path::PathBuf;
+
+/// Errors which are specific to processing Mediawiki dumps, as opposed to
+/// I/O or XML errors which are passed through as-is.
+#[derive(Debug)]
+pub enum Error {
+    /// A split dump was parsed, but no part files were given.
+    NoParts,
+    /// One part of a split dump describes a different site than the first
+    /// part, e.g., because files from two different dumps were mixed.
+    SiteInfoMismatch {
+        /// The part file which did not match.
+        path: PathBuf,
+        /// The name of the siteinfo field which differs, e.g., "dbname".
+        field: &'static str,
+        /// The value found in the first part.
+        expected: String,
+        /// The value found in `path`.
+        found: String,
+    },
+    /// The parse was cancelled with a
+    /// [CancellationToken](struct.CancellationToken.html).
+    Cancelled,
+    /// A checkpoint to resume from does not fit the dump, e.g., because it
+    /// was taken while reading another dump.
+    InvalidCheckpoint,
+    /// The XML of a dump is malformed, e.g., because the dump is truncated.
+    Xml {
+        /// The byte offset in the (uncompressed) dump where the problem was
+        /// found.
+        position: u64,
+        /// A description of the problem.
+        message: String,
+    },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::NoParts => write!(f, "no dump parts were given"),
+            Error::Cancelled => write!(f, "parse was cancelled"),
+            Error::InvalidCheckpoint => write!(f, "checkpoint does not fit the dump"),
+            Error::SiteInfoMismatch {
+                path,
+                field,
+                expected,
+                found,
+            } => write!(
+                f,
+                "siteinfo of {} does not match the first part: {} is {:?}, expected {:?}",
+                path.display(),
+                field,
+                found,
+                expected
+            ),
+            Error::Xml { position, message } => {
+                write!(f, "malformed XML at byte {}: {}", position, message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {}
@@ -0,0 +1,26 @@
+//! Writers which export parsed pages to other file formats, so that they can
+//! be used by other tools without any glue code.
+//!
+//! Apart from [DumpWriter](struct.DumpWriter.html) and
+//! [ExtractorWriter](struct.ExtractorWriter.html), each writer is enabled by a
+//! cargo feature, see the [crate documentation](../index.html#features).
+
+#[cfg(feature = "arrow")]
+mod arrow;
+mod extractor;
+mod file;
+#[cfg(feature = "json")]
+mod jsonl;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+mod xml;
+
+#[cfg(feature = "arrow")]
+pub use self::arrow::{BatchBuilder, ParquetWriter};
+pub use self::extractor::ExtractorWriter;
+pub use self::file::OutputFile;
+#[cfg(feature = "json")]
+pub use self::jsonl::{Field, JsonlWriter};
+#[cfg(feature = "sqlite")]
+pub use self::sqlite::SqliteWriter;
+pub use self::xml::DumpWriter;
@@ -0,0 +1,256 @@
+use crate::{Exception, Page, Site, TextContent};
+use arrow_array::builder::{Int32Builder, StringBuilder, TimestampSecondBuilder, UInt64Builder};
+use arrow_array::{ArrayRef, RecordBatch};
+use arrow_schema::{DataType, Field, Schema, SchemaRef, TimeUnit};
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::file::properties::WriterProperties;
+use std::io::Write;
+use std::sync::Arc;
+
+/// Collects revisions into Arrow record batches, with one row for each
+/// revision. Every batch has the same schema, see
+/// [schema](struct.BatchBuilder.html#method.schema).
+///
+/// # Example
+/// ```rust
+/// use wikidump::export::BatchBuilder;
+/// use wikidump::Parser;
+///
+/// let parser = Parser::new();
+/// let site = parser.parse_file("tests/simplewiki.xml").unwrap();
+///
+/// let mut builder = BatchBuilder::new();
+/// builder.push_site(&site);
+///
+/// let batch = builder.finish();
+/// assert_eq!(batch.num_rows(), 6);
+/// ```
+pub struct BatchBuilder {
+    page_id: UInt64Builder,
+    namespace: Int32Builder,
+    title: StringBuilder,
+    revision_id: UInt64Builder,
+    timestamp: TimestampSecondBuilder,
+    text: StringBuilder,
+    rows: usize,
+}
+
+impl BatchBuilder {
+    /// Creates a builder with no rows.
+    pub fn new() -> BatchBuilder {
+        BatchBuilder {
+            page_id: UInt64Builder::new(),
+            namespace: Int32Builder::new(),
+            title: StringBuilder::new(),
+            revision_id: UInt64Builder::new(),
+            timestamp: TimestampSecondBuilder::new().with_timezone("UTC"),
+            text: StringBuilder::new(),
+            rows: 0,
+        }
+    }
+
+    /// Returns the schema of the record batches, which is:
+    ///
+    /// | Column        | Type                     | Nullable |
+    /// |---------------|--------------------------|----------|
+    /// | `page_id`     | `UInt64`                 | no       |
+    /// | `ns`          | `Int32`                  | no       |
+    /// | `title`       | `Utf8`                   | no       |
+    /// | `revision_id` | `UInt64`                 | no       |
+    /// | `timestamp`   | `Timestamp(Second, UTC)` | yes      |
+    /// | `text`        | `Utf8`                   | yes      |
+    ///
+    /// The text is null if it is not included in the dump, such as for
+    /// deleted revisions.
+    pub fn schema() -> SchemaRef {
+        Arc::new(Schema::new(vec![
+            Field::new("page_id", DataType::UInt64, false),
+            Field::new("ns", DataType::Int32, false),
+            Field::new("title", DataType::Utf8, false),
+            Field::new("revision_id", DataType::UInt64, false),
+            Field::new(
+                "timestamp",
+                DataType::Timestamp(TimeUnit::Second, Some("UTC".into())),
+                true,
+            ),
+            Field::new("text", DataType::Utf8, true),
+        ]))
+    }
+
+    /// Adds a row for each revision of a page.
+    pub fn push_page(&mut self, page: &Page) {
+        for revision in &page.revisions {
+            self.page_id.append_value(page.id);
+            self.namespace.append_value(page.namespace);
+            self.title.append_value(&page.title);
+            self.revision_id.append_value(revision.id);
+            self.timestamp
+                .append_option(parse_timestamp(&revision.timestamp));
+
+            if revision.text_content == TextContent::Included {
+                self.text.append_value(&revision.text);
+            } else {
+                self.text.append_null();
+            }
+
+            self.rows += 1;
+        }
+    }
+
+    /// Adds a row for each revision of every page of a site.
+    pub fn push_site(&mut self, site: &Site) {
+        site.pages.iter().for_each(|page| self.push_page(page));
+    }
+
+    /// Returns the number of rows which have been added since the last batch
+    /// was finished.
+    pub fn len(&self) -> usize {
+        self.rows
+    }
+
+    /// Returns true if no rows have been added since the last batch was
+    /// finished.
+    pub fn is_empty(&self) -> bool {
+        self.rows == 0
+    }
+
+    /// Returns a record batch of all rows added so far, and resets the
+    /// builder so that it can be used for the next batch.
+    pub fn finish(&mut self) -> RecordBatch {
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(self.page_id.finish()),
+            Arc::new(self.namespace.finish()),
+            Arc::new(self.title.finish()),
+            Arc::new(self.revision_id.finish()),
+            Arc::new(self.timestamp.finish()),
+            Arc::new(self.text.finish()),
+        ];
+        self.rows = 0;
+
+        RecordBatch::try_new(BatchBuilder::schema(), columns)
+            .expect("Columns do not match the schema")
+    }
+}
+
+impl Default for BatchBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Writes revisions to an Apache Parquet file, using the schema of
+/// [BatchBuilder](struct.BatchBuilder.html). Rows are buffered in memory
+/// until there are enough to fill a row group, which is then written out.
+///
+/// The file is only complete once [close](#method.close) has been called.
+///
+/// # Example
+/// ```rust
+/// use wikidump::export::ParquetWriter;
+/// use wikidump::Parser;
+/// use std::fs::File;
+///
+/// let parser = Parser::new();
+/// let site = parser.parse_file("tests/simplewiki.xml").unwrap();
+///
+/// let file = File::create(std::env::temp_dir().join("simplewiki.parquet")).unwrap();
+/// let mut writer = ParquetWriter::new(file).unwrap();
+/// writer.write_site(&site).unwrap();
+/// writer.close().unwrap();
+/// ```
+pub struct ParquetWriter<W>
+where
+    W: Write + Send,
+{
+    writer: ArrowWriter<W>,
+    batch: BatchBuilder,
+    row_group_size: usize,
+}
+
+impl<W> ParquetWriter<W>
+where
+    W: Write + Send,
+{
+    /// The number of rows in each row group, unless another size is given
+    /// with [with_row_group_size](#method.with_row_group_size). This is kept
+    /// fairly small, since every row holds the full text of a revision.
+    pub const DEFAULT_ROW_GROUP_SIZE: usize = 10_000;
+
+    /// Creates a writer with the default row group size, using Snappy
+    /// compression.
+    pub fn new(writer: W) -> Result<ParquetWriter<W>, Exception> {
+        Self::with_row_group_size(writer, Self::DEFAULT_ROW_GROUP_SIZE)
+    }
+
+    /// Creates a writer which writes row groups of the given number of rows,
+    /// using Snappy compression.
+    pub fn with_row_group_size(
+        writer: W,
+        row_group_size: usize,
+    ) -> Result<ParquetWriter<W>, Exception> {
+        let row_group_size = row_group_size.max(1);
+        let properties = WriterProperties::builder()
+            .set_compression(Compression::SNAPPY)
+            .set_max_row_group_size(row_group_size)
+            .build();
+
+        Ok(ParquetWriter {
+            writer: ArrowWriter::try_new(writer, BatchBuilder::schema(), Some(properties))?,
+            batch: BatchBuilder::new(),
+            row_group_size,
+        })
+    }
+
+    /// Writes a row for each revision of a page.
+    pub fn write_page(&mut self, page: &Page) -> Result<(), Exception> {
+        self.batch.push_page(page);
+
+        if self.batch.len() >= self.row_group_size {
+            self.writer.write(&self.batch.finish())?;
+            self.writer.flush()?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes a row for each revision of every page of a site.
+    pub fn write_site(&mut self, site: &Site) -> Result<(), Exception> {
+        site.pages.iter().try_for_each(|page| self.write_page(page))
+    }
+
+    /// Writes any remaining rows and the file footer, and returns the
+    /// underlying writer.
+    pub fn close(mut self) -> Result<W, Exception> {
+        if !self.batch.is_empty() {
+            self.writer.write(&self.batch.finish())?;
+        }
+
+        Ok(self.writer.into_inner()?)
+    }
+}
+
+/// Converts a timestamp in the format used by dumps, e.g.,
+/// "2019-04-26T03:40:24Z", to the number of seconds since the Unix epoch.
+fn parse_timestamp(timestamp: &str) -> Option<i64> {
+    let number =
+        |start: usize, end: usize| -> Option<i64> { timestamp.get(start..end)?.parse().ok() };
+
+    if timestamp.len() != 20 || !timestamp.ends_with('Z') {
+        return None;
+    }
+
+    let (year, month, day) = (number(0, 4)?, number(5, 7)?, number(8, 10)?);
+    let (hour, minute, second) = (number(11, 13)?, number(14, 16)?, number(17, 19)?);
+
+    // Days since the epoch for a date in the proleptic Gregorian calendar, see
+    // http://howardhinnant.github.io/date_algorithms.html#days_from_civil
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = era * 146_097 + day_of_era - 719_468;
+
+    Some(days * 86_400 + hour * 3_600 + minute * 60 + second)
+}
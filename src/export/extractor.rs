@@ -0,0 +1,199 @@
+use crate::export::OutputFile;
+use crate::{Exception, Page, Site, TextContent};
+use quick_xml::escape::partial_escape;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// The number of files written to each directory before moving on to the
+/// next one, which is the same as WikiExtractor.
+const FILES_PER_DIR: usize = 100;
+
+/// The number of directories which can be named with two letters.
+const MAX_DIRS: usize = 26 * 26;
+
+/// Writes pages in the output format of
+/// [WikiExtractor](https://github.com/attardi/wikiextractor), so that tools
+/// which consume its output can be used with this crate.
+///
+/// Each page is written as a document of the form:
+///
+/// ```text
+/// <doc id="6" url="https://simple.wikipedia.org/wiki?curid=6" title="Art">
+/// Art
+///
+/// Art is a creative activity...
+/// </doc>
+/// ```
+///
+/// As with the `--html-safe` option of WikiExtractor, `&`, `<` and `>` are
+/// escaped in the title and text, and so are quotes in the title attribute.
+///
+/// The URL of each page is built from the `url` of the site given to
+/// [write_site_info](#method.write_site_info). The text is taken from the
+/// last revision of each page, so the parser should be configured to process
+/// wiki text. Pages without any included text are not written.
+///
+/// Documents are written to files named `AA/wiki_00`, `AA/wiki_01` and so
+/// on, up to `AA/wiki_99` before moving on to `AB/wiki_00`. A new file is
+/// started when the current one would become larger than
+/// [max_file_size](#method.max_file_size).
+///
+/// # Example
+/// ```rust
+/// use wikidump::export::ExtractorWriter;
+/// use wikidump::Parser;
+///
+/// let parser = Parser::new();
+/// let site = parser.parse_file("tests/simplewiki.xml").unwrap();
+///
+/// let output = std::env::temp_dir().join("wikidump-extractor-example");
+/// let mut writer = ExtractorWriter::create(&output).unwrap();
+/// writer.write_site(&site).unwrap();
+/// writer.finish().unwrap();
+///
+/// let docs = std::fs::read_to_string(output.join("AA").join("wiki_00")).unwrap();
+/// assert!(docs.starts_with("<doc id=\"1\" url=\"https://simple.wikipedia.org/wiki?curid=1\" title=\"April\">\nApril\n\n"));
+/// # std::fs::remove_dir_all(&output).unwrap();
+/// ```
+pub struct ExtractorWriter {
+    directory: PathBuf,
+    max_file_size: u64,
+    compress: bool,
+    url_base: String,
+    /// The number of files which have been opened so far.
+    files: usize,
+    file: Option<OutputFile>,
+    /// The number of uncompressed bytes written to the current file.
+    file_size: u64,
+}
+
+impl ExtractorWriter {
+    /// The maximum size of each file, unless another size is given with
+    /// [max_file_size](#method.max_file_size). This is the same as the
+    /// default of WikiExtractor.
+    pub const DEFAULT_MAX_FILE_SIZE: u64 = 1024 * 1024;
+
+    /// Creates a writer which writes files to a directory. The directory is
+    /// created if it does not exist yet.
+    pub fn create<P>(directory: P) -> Result<ExtractorWriter, Exception>
+    where
+        P: AsRef<Path>,
+    {
+        fs::create_dir_all(directory.as_ref())?;
+
+        Ok(ExtractorWriter {
+            directory: directory.as_ref().to_path_buf(),
+            max_file_size: Self::DEFAULT_MAX_FILE_SIZE,
+            compress: false,
+            url_base: String::new(),
+            files: 0,
+            file: None,
+            file_size: 0,
+        })
+    }
+
+    /// Sets the maximum size of each file in bytes, before compression. A
+    /// document which is larger than this is written to a file of its own.
+    pub fn max_file_size(mut self, bytes: u64) -> Self {
+        self.max_file_size = bytes.max(1);
+        self
+    }
+
+    /// Sets whether files are compressed with bzip2, in which case they are
+    /// named `wiki_00.bz2` and so on.
+    pub fn compress(mut self, value: bool) -> Self {
+        self.compress = value;
+        self
+    }
+
+    /// Uses the URL of a site to build the URLs of the pages which are
+    /// written after this.
+    pub fn write_site_info(&mut self, site: &Site) -> Result<(), Exception> {
+        self.url_base = match site.url.rfind('/') {
+            Some(index) => site.url[..index].to_string(),
+            None => site.url.clone(),
+        };
+
+        Ok(())
+    }
+
+    /// Writes the last revision of a page as a document.
+    pub fn write_page(&mut self, page: &Page) -> Result<(), Exception> {
+        let revision = match page.revisions.last() {
+            Some(revision) if revision.text_content == TextContent::Included => revision,
+            _ => return Ok(()),
+        };
+
+        let document = format!(
+            "<doc id=\"{id}\" url=\"{base}?curid={id}\" title=\"{attribute}\">\n{title}\n\n{text}\n</doc>\n",
+            id = page.id,
+            base = self.url_base,
+            attribute = partial_escape(&page.title).replace('"', "&quot;"),
+            title = partial_escape(&page.title),
+            text = partial_escape(&revision.text),
+        );
+
+        if self.file.is_none()
+            || (self.file_size > 0 && self.file_size + document.len() as u64 > self.max_file_size)
+        {
+            self.next_file()?;
+        }
+
+        if let Some(file) = self.file.as_mut() {
+            file.write_all(document.as_bytes())?;
+            self.file_size += document.len() as u64;
+        }
+
+        Ok(())
+    }
+
+    /// Writes every page of a site, using its URL for the pages.
+    pub fn write_site(&mut self, site: &Site) -> Result<(), Exception> {
+        self.write_site_info(site)?;
+        site.pages.iter().try_for_each(|page| self.write_page(page))
+    }
+
+    /// Flushes and closes the current file. For compressed files, this also
+    /// ends the bzip2 stream.
+    pub fn finish(mut self) -> Result<(), Exception> {
+        self.close_file()?;
+        Ok(())
+    }
+
+    fn next_file(&mut self) -> Result<(), Exception> {
+        self.close_file()?;
+
+        let dir_index = self.files / FILES_PER_DIR;
+        if dir_index >= MAX_DIRS {
+            return Err(Box::new(io::Error::other(
+                "Too many output files for WikiExtractor directory names",
+            )));
+        }
+
+        let letters = [
+            (b'A' + (dir_index / 26) as u8) as char,
+            (b'A' + (dir_index % 26) as u8) as char,
+        ];
+        let directory = self.directory.join(letters.iter().collect::<String>());
+        fs::create_dir_all(&directory)?;
+
+        let mut name = format!("wiki_{:02}", self.files % FILES_PER_DIR);
+        if self.compress {
+            name.push_str(".bz2");
+        }
+
+        self.file = Some(OutputFile::create(&directory.join(name), self.compress)?);
+        self.files += 1;
+        self.file_size = 0;
+
+        Ok(())
+    }
+
+    fn close_file(&mut self) -> io::Result<()> {
+        match self.file.take() {
+            Some(mut file) => file.try_finish(),
+            None => Ok(()),
+        }
+    }
+}
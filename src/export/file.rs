@@ -0,0 +1,59 @@
+use bzip2::write::BzEncoder;
+use bzip2::Compression;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+/// A file written by an exporter, which is optionally compressed with bzip2.
+///
+/// A compressed file is only complete once [try_finish](#method.try_finish)
+/// has been called, which the writers do when they are finished.
+pub struct OutputFile {
+    inner: Inner,
+}
+
+enum Inner {
+    Plain(BufWriter<File>),
+    Compressed(BzEncoder<BufWriter<File>>),
+}
+
+impl OutputFile {
+    /// Creates a file, which is compressed with bzip2 if `compress` is true.
+    pub(crate) fn create(path: &Path, compress: bool) -> io::Result<OutputFile> {
+        let file = BufWriter::new(File::create(path)?);
+        let inner = if compress {
+            Inner::Compressed(BzEncoder::new(file, Compression::best()))
+        } else {
+            Inner::Plain(file)
+        };
+
+        Ok(OutputFile { inner })
+    }
+
+    /// Ends the bzip2 stream of a compressed file, and flushes the file.
+    pub fn try_finish(&mut self) -> io::Result<()> {
+        match &mut self.inner {
+            Inner::Plain(file) => file.flush(),
+            Inner::Compressed(encoder) => {
+                encoder.try_finish()?;
+                encoder.get_mut().flush()
+            }
+        }
+    }
+}
+
+impl Write for OutputFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match &mut self.inner {
+            Inner::Plain(file) => file.write(buf),
+            Inner::Compressed(encoder) => encoder.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match &mut self.inner {
+            Inner::Plain(file) => file.flush(),
+            Inner::Compressed(encoder) => encoder.flush(),
+        }
+    }
+}
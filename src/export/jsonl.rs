@@ -0,0 +1,204 @@
+use crate::{Page, PageRevision, Site, TextContent};
+use serde_json::{Map, Value};
+use std::io::{self, Write};
+
+/// A property of a page or one of its revisions which can be exported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    /// The title of the page, as `"title"`.
+    Title,
+    /// The ID of the page, as `"id"`.
+    Id,
+    /// The namespace of the page, as `"ns"`.
+    Namespace,
+    /// The ID of the revision, as `"revision_id"`.
+    RevisionId,
+    /// The ID of the parent revision, as `"parent_id"`.
+    ParentId,
+    /// The time the revision was made, as `"timestamp"`.
+    Timestamp,
+    /// The name or IP address of the contributor, as `"contributor"`.
+    Contributor,
+    /// The edit summary of the revision, as `"comment"`.
+    Comment,
+    /// The (possibly processed) text of the revision, as `"text"`.
+    Text,
+    /// The raw wiki text of the revision, as `"raw"`.
+    Raw,
+    /// The categories of the revision, as `"categories"`.
+    Categories,
+}
+
+impl Field {
+    /// Returns the key used for the field in exported objects.
+    pub fn name(self) -> &'static str {
+        match self {
+            Field::Title => "title",
+            Field::Id => "id",
+            Field::Namespace => "ns",
+            Field::RevisionId => "revision_id",
+            Field::ParentId => "parent_id",
+            Field::Timestamp => "timestamp",
+            Field::Contributor => "contributor",
+            Field::Comment => "comment",
+            Field::Text => "text",
+            Field::Raw => "raw",
+            Field::Categories => "categories",
+        }
+    }
+
+    /// Returns true if this is a property of the page, rather than of one of
+    /// its revisions.
+    fn is_page_field(self) -> bool {
+        matches!(self, Field::Title | Field::Id | Field::Namespace)
+    }
+
+    fn page_value(self, page: &Page) -> Value {
+        match self {
+            Field::Title => Value::from(page.title.as_str()),
+            Field::Id => Value::from(page.id),
+            Field::Namespace => Value::from(page.namespace),
+            _ => Value::Null,
+        }
+    }
+
+    fn revision_value(self, revision: &PageRevision) -> Value {
+        let included = revision.text_content == TextContent::Included;
+
+        match self {
+            Field::RevisionId => Value::from(revision.id),
+            Field::ParentId => Value::from(revision.parent_id),
+            Field::Timestamp => Value::from(revision.timestamp.as_str()),
+            Field::Contributor => Value::from(revision.contributor.name()),
+            Field::Comment => Value::from(revision.comment.text()),
+            Field::Text if included => Value::from(revision.text.as_str()),
+            Field::Raw if included => Value::from(revision.raw.as_str()),
+            Field::Categories => Value::from(revision.categories.clone()),
+            _ => Value::Null,
+        }
+    }
+}
+
+/// Writes pages as [JSON Lines](https://jsonlines.org/), with one JSON
+/// object on each line.
+///
+/// By default, one object is written for each page, containing the selected
+/// page fields and a `"revisions"` array with the selected revision fields.
+/// Alternatively, one object can be written for each revision, containing
+/// both the page and revision fields.
+///
+/// Text which is not included in the dump, such as deleted text, is written
+/// as `null` rather than as an empty string.
+///
+/// # Example
+/// ```rust
+/// use wikidump::export::{Field, JsonlWriter};
+/// use wikidump::Parser;
+///
+/// let parser = Parser::new();
+/// let site = parser.parse_file("tests/simplewiki.xml").unwrap();
+///
+/// let mut writer = JsonlWriter::new(Vec::new())
+///     .fields(&[Field::Title, Field::Text])
+///     .per_revision(true);
+/// writer.write_site(&site).unwrap();
+///
+/// let output = String::from_utf8(writer.into_inner()).unwrap();
+/// assert!(output.starts_with(r#"{"title":"April","text":"#));
+/// ```
+pub struct JsonlWriter<W> {
+    writer: W,
+    fields: Vec<Field>,
+    per_revision: bool,
+}
+
+impl<W> JsonlWriter<W>
+where
+    W: Write,
+{
+    /// Creates a writer which writes one object for each page, with its
+    /// title, ID, namespace, and the text of each revision.
+    pub fn new(writer: W) -> JsonlWriter<W> {
+        JsonlWriter {
+            writer,
+            fields: vec![Field::Title, Field::Id, Field::Namespace, Field::Text],
+            per_revision: false,
+        }
+    }
+
+    /// Sets the fields which are written, in order.
+    pub fn fields(mut self, fields: &[Field]) -> Self {
+        self.fields = fields.to_vec();
+        self
+    }
+
+    /// Sets whether one object is written for each revision, rather than
+    /// one for each page.
+    ///
+    /// Writing one object for each page is the default.
+    pub fn per_revision(mut self, value: bool) -> Self {
+        self.per_revision = value;
+        self
+    }
+
+    /// Writes a single page, as one line or one line per revision.
+    pub fn write_page(&mut self, page: &Page) -> io::Result<()> {
+        let mut object = Map::new();
+
+        for &field in self.fields.iter().filter(|f| f.is_page_field()) {
+            object.insert(field.name().to_string(), field.page_value(page));
+        }
+
+        if self.per_revision {
+            for revision in &page.revisions {
+                let mut object = object.clone();
+                self.insert_revision_fields(&mut object, revision);
+                self.write_line(object)?;
+            }
+
+            return Ok(());
+        }
+
+        if self.fields.iter().any(|f| !f.is_page_field()) {
+            let revisions = page
+                .revisions
+                .iter()
+                .map(|revision| {
+                    let mut object = Map::new();
+                    self.insert_revision_fields(&mut object, revision);
+                    Value::Object(object)
+                })
+                .collect();
+
+            object.insert("revisions".to_string(), Value::Array(revisions));
+        }
+
+        self.write_line(object)
+    }
+
+    /// Writes every page of a site.
+    pub fn write_site(&mut self, site: &Site) -> io::Result<()> {
+        site.pages.iter().try_for_each(|page| self.write_page(page))
+    }
+
+    /// Flushes the underlying writer.
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    /// Returns the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    fn insert_revision_fields(&self, object: &mut Map<String, Value>, revision: &PageRevision) {
+        for &field in self.fields.iter().filter(|f| !f.is_page_field()) {
+            object.insert(field.name().to_string(), field.revision_value(revision));
+        }
+    }
+
+    fn write_line(&mut self, object: Map<String, Value>) -> io::Result<()> {
+        serde_json::to_writer(&mut self.writer, &Value::Object(object))?;
+        self.writer.write_all(b"\n")
+    }
+}
@@ -0,0 +1,230 @@
+use crate::{Exception, Page, Site, TextContent};
+use rusqlite::{params, Connection};
+use std::path::Path;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS site (
+    name TEXT NOT NULL,
+    dbname TEXT NOT NULL,
+    url TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS pages (
+    id INTEGER PRIMARY KEY,
+    ns INTEGER NOT NULL,
+    title TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS revisions (
+    id INTEGER PRIMARY KEY,
+    page_id INTEGER NOT NULL REFERENCES pages (id),
+    parent_id INTEGER,
+    timestamp TEXT NOT NULL,
+    contributor TEXT,
+    comment TEXT,
+    text TEXT,
+    raw TEXT
+);
+CREATE TABLE IF NOT EXISTS links (
+    revision_id INTEGER NOT NULL REFERENCES revisions (id),
+    target TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS categories (
+    revision_id INTEGER NOT NULL REFERENCES revisions (id),
+    name TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS revisions_page_id ON revisions (page_id);
+CREATE INDEX IF NOT EXISTS links_revision_id ON links (revision_id);
+CREATE INDEX IF NOT EXISTS links_target ON links (target);
+CREATE INDEX IF NOT EXISTS categories_revision_id ON categories (revision_id);
+CREATE INDEX IF NOT EXISTS categories_name ON categories (name);
+CREATE VIRTUAL TABLE IF NOT EXISTS revisions_fts USING fts5 (
+    text,
+    content = 'revisions',
+    content_rowid = 'id'
+);
+";
+
+/// Writes pages to a SQLite database, so that a dump can be queried as a
+/// single file. The database has the following tables:
+///
+/// * `site`: The name, database name and URL of the site.
+/// * `pages`: The ID, namespace and title of each page.
+/// * `revisions`: The metadata, text and raw wiki text of each revision.
+///   Text which is not included in the dump is `NULL`.
+/// * `links`: The target of each internal link, by revision.
+/// * `categories`: The name of each category, by revision.
+/// * `revisions_fts`: A full-text search index over the text of each
+///   revision, using FTS5.
+///
+/// Pages are inserted in transactions of
+/// [batch_size](#method.batch_size) pages at a time. Any pages which have not
+/// been committed yet are discarded unless [finish](#method.finish) is
+/// called.
+///
+/// # Example
+/// ```rust
+/// use wikidump::export::SqliteWriter;
+/// use wikidump::Parser;
+///
+/// let parser = Parser::new();
+/// let site = parser.parse_file("tests/simplewiki.xml").unwrap();
+///
+/// let mut writer = SqliteWriter::open_in_memory().unwrap();
+/// writer.write_site(&site).unwrap();
+/// let connection = writer.finish().unwrap();
+///
+/// let title: String = connection
+///     .query_row(
+///         "SELECT title FROM pages JOIN revisions ON revisions.page_id = pages.id
+///          WHERE revisions.id IN (SELECT rowid FROM revisions_fts WHERE revisions_fts MATCH 'crafts')",
+///         [],
+///         |row| row.get(0),
+///     )
+///     .unwrap();
+/// assert_eq!(title, "Art");
+/// ```
+pub struct SqliteWriter {
+    connection: Connection,
+    batch_size: usize,
+    /// The number of pages written in the current transaction.
+    pending: usize,
+}
+
+impl SqliteWriter {
+    /// The number of pages inserted in each transaction, unless another size
+    /// is given with [batch_size](#method.batch_size).
+    pub const DEFAULT_BATCH_SIZE: usize = 1_000;
+
+    /// Opens or creates a database file, and creates the tables if they do
+    /// not exist yet.
+    pub fn open<P>(path: P) -> Result<SqliteWriter, Exception>
+    where
+        P: AsRef<Path>,
+    {
+        Self::from_connection(Connection::open(path)?)
+    }
+
+    /// Creates a database in memory, which is mostly useful for testing.
+    pub fn open_in_memory() -> Result<SqliteWriter, Exception> {
+        Self::from_connection(Connection::open_in_memory()?)
+    }
+
+    /// Uses an existing database connection, and creates the tables if they
+    /// do not exist yet.
+    pub fn from_connection(connection: Connection) -> Result<SqliteWriter, Exception> {
+        connection.execute_batch(SCHEMA)?;
+
+        Ok(SqliteWriter {
+            connection,
+            batch_size: Self::DEFAULT_BATCH_SIZE,
+            pending: 0,
+        })
+    }
+
+    /// Sets the number of pages which are inserted in each transaction.
+    /// Larger transactions are faster, but hold more data in memory.
+    pub fn batch_size(mut self, pages: usize) -> Self {
+        self.batch_size = pages.max(1);
+        self
+    }
+
+    /// Writes the name, database name and URL of a site.
+    pub fn write_site_info(&mut self, site: &Site) -> Result<(), Exception> {
+        self.connection.execute(
+            "INSERT INTO site (name, dbname, url) VALUES (?1, ?2, ?3)",
+            params![site.name, site.dbname, site.url],
+        )?;
+
+        Ok(())
+    }
+
+    /// Writes a page, along with its revisions, links and categories.
+    pub fn write_page(&mut self, page: &Page) -> Result<(), Exception> {
+        if self.pending == 0 {
+            self.connection.execute_batch("BEGIN")?;
+        }
+
+        self.connection
+            .prepare_cached(
+                "INSERT OR IGNORE INTO pages (id, ns, title) VALUES (NULLIF(?1, 0), ?2, ?3)",
+            )?
+            .execute(params![page.id, page.namespace, page.title])?;
+        let page_id = match page.id {
+            0 => self.connection.last_insert_rowid(),
+            id => id as i64,
+        };
+
+        for revision in &page.revisions {
+            let included = revision.text_content == TextContent::Included;
+            let text = Some(revision.text.as_str()).filter(|_| included);
+            let raw = Some(revision.raw.as_str()).filter(|_| included);
+
+            self.connection
+                .prepare_cached(
+                    "INSERT INTO revisions (id, page_id, parent_id, timestamp, contributor, comment, text, raw)
+                     VALUES (NULLIF(?1, 0), ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                )?
+                .execute(params![
+                    revision.id,
+                    page_id,
+                    revision.parent_id,
+                    revision.timestamp,
+                    revision.contributor.name(),
+                    revision.comment.text(),
+                    text,
+                    raw,
+                ])?;
+            let revision_id = self.connection.last_insert_rowid();
+
+            if let Some(text) = text {
+                self.connection
+                    .prepare_cached("INSERT INTO revisions_fts (rowid, text) VALUES (?1, ?2)")?
+                    .execute(params![revision_id, text])?;
+            }
+
+            let mut insert_link = self
+                .connection
+                .prepare_cached("INSERT INTO links (revision_id, target) VALUES (?1, ?2)")?;
+            for target in &revision.links {
+                insert_link.execute(params![revision_id, target])?;
+            }
+
+            let mut insert_category = self
+                .connection
+                .prepare_cached("INSERT INTO categories (revision_id, name) VALUES (?1, ?2)")?;
+            for name in &revision.categories {
+                insert_category.execute(params![revision_id, name])?;
+            }
+        }
+
+        self.pending += 1;
+
+        if self.pending >= self.batch_size {
+            self.commit()?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes the site information and every page of a site.
+    pub fn write_site(&mut self, site: &Site) -> Result<(), Exception> {
+        self.write_site_info(site)?;
+        site.pages.iter().try_for_each(|page| self.write_page(page))
+    }
+
+    /// Commits any pages which have not been committed yet, and returns the
+    /// database connection.
+    pub fn finish(mut self) -> Result<Connection, Exception> {
+        self.commit()?;
+
+        Ok(self.connection)
+    }
+
+    fn commit(&mut self) -> Result<(), Exception> {
+        if self.pending > 0 {
+            self.connection.execute_batch("COMMIT")?;
+            self.pending = 0;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,292 @@
+use crate::export::OutputFile;
+use crate::{Comment, Contributor, Exception, Page, PageRevision, Site, TextContent};
+use quick_xml::escape::partial_escape;
+use quick_xml::events::{BytesDecl, BytesEnd, BytesStart, BytesText, Event};
+use quick_xml::Writer;
+use std::io::{self, Write};
+use std::path::Path;
+
+/// The version of the export schema which is written.
+const SCHEMA_VERSION: &str = "0.11";
+
+/// Writes pages back out as a Mediawiki XML dump, which can be read by this
+/// crate as well as imported by MediaWiki and other tools. This is useful for
+/// producing small test dumps, or subsets of a dump about a certain topic.
+///
+/// The raw wiki text of each revision is written, along with the properties
+/// which this crate reads from dumps. Other properties, such as whether a
+/// revision was a minor edit, are not preserved.
+///
+/// The dump is only complete once [finish](#method.finish) has been called.
+///
+/// # Example
+/// ```rust
+/// use wikidump::export::DumpWriter;
+/// use wikidump::Parser;
+///
+/// let parser = Parser::new();
+/// let site = parser.parse_file("tests/simplewiki.xml").unwrap();
+///
+/// let mut writer = DumpWriter::new(Vec::new());
+/// writer.write_site_info(&site).unwrap();
+///
+/// for page in site.pages.iter().filter(|p| p.title.starts_with("Au")) {
+///     writer.write_page(page).unwrap();
+/// }
+///
+/// let dump = String::from_utf8(writer.finish().unwrap()).unwrap();
+/// let subset = parser.parse_str(&dump).unwrap();
+/// assert_eq!(subset.pages.len(), 2);
+/// ```
+pub struct DumpWriter<W>
+where
+    W: Write,
+{
+    writer: Writer<W>,
+    started: bool,
+    /// Completes the underlying writer once the dump has ended.
+    finish_writer: fn(&mut W) -> io::Result<()>,
+}
+
+impl DumpWriter<OutputFile> {
+    /// Creates a dump file. If the path ends with `.bz2`, then the dump is
+    /// compressed with bzip2.
+    pub fn create<P>(path: P) -> Result<DumpWriter<OutputFile>, Exception>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let compress = path.extension().is_some_and(|e| e == "bz2");
+
+        Ok(DumpWriter {
+            finish_writer: OutputFile::try_finish,
+            ..DumpWriter::new(OutputFile::create(path, compress)?)
+        })
+    }
+}
+
+impl<W> DumpWriter<W>
+where
+    W: Write,
+{
+    /// Creates a writer which writes an uncompressed dump.
+    pub fn new(writer: W) -> DumpWriter<W> {
+        DumpWriter {
+            writer: Writer::new_with_indent(writer, b' ', 2),
+            started: false,
+            finish_writer: W::flush,
+        }
+    }
+
+    /// Writes the `<siteinfo>` header of a site. If this is used, it must be
+    /// called before any pages are written.
+    pub fn write_site_info(&mut self, site: &Site) -> Result<(), Exception> {
+        self.start()?;
+        self.start_element("siteinfo")?;
+        self.text_element("sitename", &site.name)?;
+        self.text_element("dbname", &site.dbname)?;
+        self.text_element("base", &site.url)?;
+        self.text_element("generator", &site.generator)?;
+        self.text_element("case", &site.case)?;
+
+        self.start_element("namespaces")?;
+        for namespace in &site.namespaces {
+            let key = namespace.key.to_string();
+            let element = self
+                .writer
+                .create_element("namespace")
+                .with_attributes([("key", key.as_str()), ("case", namespace.case.as_str())]);
+
+            if namespace.name.is_empty() {
+                element.write_empty()?;
+            } else {
+                element.write_text_content(escape_text(&namespace.name))?;
+            }
+        }
+        self.end_element("namespaces")?;
+
+        self.end_element("siteinfo")
+    }
+
+    /// Writes a page and all of its revisions.
+    pub fn write_page(&mut self, page: &Page) -> Result<(), Exception> {
+        self.start()?;
+        self.start_element("page")?;
+        self.text_element("title", &page.title)?;
+        self.text_element("ns", &page.namespace.to_string())?;
+        self.text_element("id", &page.id.to_string())?;
+
+        for revision in &page.revisions {
+            self.write_revision(revision)?;
+        }
+
+        self.end_element("page")
+    }
+
+    /// Writes the siteinfo and every page of a site.
+    pub fn write_site(&mut self, site: &Site) -> Result<(), Exception> {
+        self.write_site_info(site)?;
+        site.pages.iter().try_for_each(|page| self.write_page(page))
+    }
+
+    /// Ends the dump, and returns the underlying writer. For a compressed
+    /// dump, this also ends the bzip2 stream.
+    pub fn finish(mut self) -> Result<W, Exception> {
+        self.start()?;
+        self.end_element("mediawiki")?;
+        self.writer.get_mut().write_all(b"\n")?;
+        (self.finish_writer)(self.writer.get_mut())?;
+
+        Ok(self.writer.into_inner())
+    }
+
+    fn write_revision(&mut self, revision: &PageRevision) -> Result<(), Exception> {
+        self.start_element("revision")?;
+        self.text_element("id", &revision.id.to_string())?;
+
+        if let Some(parent_id) = revision.parent_id {
+            self.text_element("parentid", &parent_id.to_string())?;
+        }
+
+        self.text_element("timestamp", &revision.timestamp)?;
+
+        match &revision.contributor {
+            Contributor::User { id, username } => {
+                self.start_element("contributor")?;
+                self.text_element("username", username)?;
+                self.text_element("id", &id.to_string())?;
+                self.end_element("contributor")?;
+            }
+            Contributor::Anonymous { ip } => {
+                self.start_element("contributor")?;
+                self.text_element("ip", ip)?;
+                self.end_element("contributor")?;
+            }
+            Contributor::Deleted => self.deleted_element("contributor")?,
+            Contributor::Unknown => {}
+        }
+
+        match &revision.comment {
+            Comment::Text(text) => self.text_element("comment", text)?,
+            Comment::Deleted => self.deleted_element("comment")?,
+            Comment::Empty => {}
+        }
+
+        if let Some(origin) = revision.origin {
+            self.text_element("origin", &origin.to_string())?;
+        }
+        if !revision.model.is_empty() {
+            self.text_element("model", &revision.model)?;
+        }
+        if !revision.format.is_empty() {
+            self.text_element("format", &revision.format)?;
+        }
+
+        match revision.text_content {
+            TextContent::Included => {
+                let bytes = revision.raw.len().to_string();
+                self.writer
+                    .create_element("text")
+                    .with_attributes([("bytes", bytes.as_str()), ("xml:space", "preserve")])
+                    .write_text_content(escape_text(&revision.raw))?;
+            }
+            TextContent::NotIncluded => {
+                let mut element = BytesStart::new("text");
+                if let Some(bytes) = revision.text_bytes {
+                    element.push_attribute(("bytes", bytes.to_string().as_str()));
+                }
+                if let Some(id) = revision.text_id {
+                    element.push_attribute(("id", id.to_string().as_str()));
+                }
+                self.writer.write_event(Event::Empty(element))?;
+            }
+            TextContent::Deleted => self.deleted_element("text")?,
+        }
+
+        if !revision.sha1.is_empty() {
+            self.text_element("sha1", &revision.sha1)?;
+        }
+
+        for slot in &revision.slots {
+            self.start_element("content")?;
+            self.text_element("role", &slot.role)?;
+            if let Some(origin) = slot.origin {
+                self.text_element("origin", &origin.to_string())?;
+            }
+            self.text_element("model", &slot.model)?;
+            self.text_element("format", &slot.format)?;
+
+            let bytes = slot.text.len().to_string();
+            self.writer
+                .create_element("text")
+                .with_attributes([("bytes", bytes.as_str()), ("xml:space", "preserve")])
+                .write_text_content(escape_text(&slot.text))?;
+            self.end_element("content")?;
+        }
+
+        self.end_element("revision")
+    }
+
+    /// Writes the XML declaration and the root element, if that has not been
+    /// done yet.
+    fn start(&mut self) -> Result<(), Exception> {
+        if self.started {
+            return Ok(());
+        }
+
+        let namespace = format!("http://www.mediawiki.org/xml/export-{}/", SCHEMA_VERSION);
+        let location = format!(
+            "{} http://www.mediawiki.org/xml/export-{}.xsd",
+            namespace, SCHEMA_VERSION
+        );
+
+        self.writer
+            .write_event(Event::Decl(BytesDecl::new("1.0", Some("utf-8"), None)))?;
+        self.writer
+            .write_event(Event::Start(BytesStart::new("mediawiki").with_attributes(
+                [
+                    ("xmlns", namespace.as_str()),
+                    ("xmlns:xsi", "http://www.w3.org/2001/XMLSchema-instance"),
+                    ("xsi:schemaLocation", location.as_str()),
+                    ("version", SCHEMA_VERSION),
+                ],
+            )))?;
+        self.started = true;
+
+        Ok(())
+    }
+
+    fn start_element(&mut self, name: &str) -> Result<(), Exception> {
+        self.writer
+            .write_event(Event::Start(BytesStart::new(name)))?;
+        Ok(())
+    }
+
+    fn end_element(&mut self, name: &str) -> Result<(), Exception> {
+        self.writer.write_event(Event::End(BytesEnd::new(name)))?;
+        Ok(())
+    }
+
+    fn text_element(&mut self, name: &str, text: &str) -> Result<(), Exception> {
+        self.writer
+            .create_element(name)
+            .write_text_content(escape_text(text))?;
+        Ok(())
+    }
+
+    /// Writes an empty element which is marked as deleted, which is how dumps
+    /// hide content that has been deleted or suppressed.
+    fn deleted_element(&mut self, name: &str) -> Result<(), Exception> {
+        self.writer
+            .create_element(name)
+            .with_attribute(("deleted", "deleted"))
+            .write_empty()?;
+        Ok(())
+    }
+}
+
+/// Escapes text the way MediaWiki does, which leaves quotes as they are since
+/// they only need to be escaped in attribute values.
+fn escape_text(text: &str) -> BytesText<'_> {
+    BytesText::from_escaped(partial_escape(text))
+}
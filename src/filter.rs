@@ -0,0 +1,115 @@
+use crate::Exception;
+use std::collections::HashSet;
+use std::fmt;
+use std::path::Path;
+
+/// Decides which pages the parser reads, by their title. Pages whose title
+/// does not match are skipped as soon as their title has been read, so
+/// their text is never unescaped or processed.
+///
+/// See [Parser::filter_titles](struct.Parser.html#method.filter_titles).
+///
+/// # Example
+/// ```rust
+/// use wikidump::{Parser, TitleFilter};
+///
+/// let parser = Parser::new().filter_titles(TitleFilter::exact(["Art", "April"]));
+/// let site = parser.parse_file("tests/simplewiki.xml").unwrap();
+///
+/// let titles = site.pages.iter().map(|p| p.title.as_str()).collect::<Vec<_>>();
+/// assert_eq!(titles, ["April", "Art"]);
+/// ```
+pub struct TitleFilter {
+    matcher: Matcher,
+}
+
+enum Matcher {
+    Exact(HashSet<String>),
+    Prefix(String),
+    #[cfg(feature = "regex")]
+    Regex(regex::Regex),
+    Custom(Box<dyn Fn(&str) -> bool + Send + Sync>),
+}
+
+impl TitleFilter {
+    /// Matches the given titles exactly. Underscores are turned into spaces,
+    /// like in the titles of a dump, so titles can be copied from URLs.
+    pub fn exact<I, T>(titles: I) -> TitleFilter
+    where
+        I: IntoIterator<Item = T>,
+        T: AsRef<str>,
+    {
+        let titles = titles
+            .into_iter()
+            .map(|t| t.as_ref().trim().replace('_', " "))
+            .collect();
+
+        TitleFilter {
+            matcher: Matcher::Exact(titles),
+        }
+    }
+
+    /// Matches the titles listed in a file, one title per line, like
+    /// [exact](#method.exact). Empty lines are ignored.
+    pub fn from_file<P>(path: P) -> Result<TitleFilter, Exception>
+    where
+        P: AsRef<Path>,
+    {
+        let titles = std::fs::read_to_string(path)?;
+        let titles = titles.lines().filter(|line| !line.trim().is_empty());
+
+        Ok(TitleFilter::exact(titles))
+    }
+
+    /// Matches titles which start with `prefix`, e.g., "List of".
+    pub fn prefix(prefix: &str) -> TitleFilter {
+        TitleFilter {
+            matcher: Matcher::Prefix(prefix.to_string()),
+        }
+    }
+
+    /// Matches titles which match a regular expression anywhere in the
+    /// title. Use `^` and `$` to match the whole title.
+    ///
+    /// This is only available with the `regex` feature.
+    #[cfg(feature = "regex")]
+    pub fn regex(pattern: &str) -> Result<TitleFilter, regex::Error> {
+        Ok(TitleFilter {
+            matcher: Matcher::Regex(regex::Regex::new(pattern)?),
+        })
+    }
+
+    /// Matches titles for which `matches` returns true. Like renderers, the
+    /// function is shared by every thread, so it must be `Send` and `Sync`.
+    pub fn custom<F>(matches: F) -> TitleFilter
+    where
+        F: Fn(&str) -> bool + Send + Sync + 'static,
+    {
+        TitleFilter {
+            matcher: Matcher::Custom(Box::new(matches)),
+        }
+    }
+
+    /// Returns true if pages with the given title should be read.
+    pub fn matches(&self, title: &str) -> bool {
+        match &self.matcher {
+            Matcher::Exact(titles) => titles.contains(title),
+            Matcher::Prefix(prefix) => title.starts_with(prefix.as_str()),
+            #[cfg(feature = "regex")]
+            Matcher::Regex(regex) => regex.is_match(title),
+            Matcher::Custom(matches) => matches(title),
+        }
+    }
+}
+
+impl fmt::Debug for TitleFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.matcher {
+            Matcher::Exact(titles) => f.debug_tuple("Exact").field(titles).finish(),
+            Matcher::Prefix(prefix) => f.debug_tuple("Prefix").field(prefix).finish(),
+            #[cfg(feature = "regex")]
+            Matcher::Regex(regex) => f.debug_tuple("Regex").field(regex).finish(),
+            Matcher::Custom(_) => f.write_str("Custom"),
+        }
+    }
+}
@@ -22,23 +22,78 @@
 //!     }
 //! }
 //! ```
+//!
+//! # Features
+//! * `serde`: Implements `Serialize` and `Deserialize` for `Site`, `Page`,
+//!   `PageRevision` and the types they contain, so that parsed data can be
+//!   cached or sent elsewhere.
+//! * `json`: Enables [export::JsonlWriter](export/struct.JsonlWriter.html),
+//!   which writes pages as JSON Lines.
+//! * `arrow`: Enables [export::BatchBuilder](export/struct.BatchBuilder.html)
+//!   and [export::ParquetWriter](export/struct.ParquetWriter.html), which
+//!   convert pages to Apache Arrow record batches and Parquet files.
+//! * `sqlite`: Enables [export::SqliteWriter](export/struct.SqliteWriter.html),
+//!   which writes pages to a SQLite database with a full-text search index.
+//! * `regex`: Enables [TitleFilter::regex](struct.TitleFilter.html#method.regex),
+//!   which reads only the pages whose title matches a regular expression.
 
+mod budget;
+mod cancellation;
+mod checkpoint;
 pub mod config;
-use bzip2::read::MultiBzDecoder;
+mod diff;
+mod error;
+pub mod export;
+mod filter;
+mod offsets;
+mod pool;
+mod progress;
+mod reader;
+pub mod render;
+mod report;
+mod selection;
+mod stream;
+mod visitor;
+pub use budget::{BudgetAction, BudgetExceeded, OverBudgetRevision};
+pub use cancellation::CancellationToken;
+pub use checkpoint::Checkpoint;
+pub use diff::{DiffRange, DiffText, DiffUnit, RevisionDiff};
+pub use error::Error;
+pub use filter::TitleFilter;
+pub use offsets::OffsetMap;
+pub use progress::Progress;
+pub use report::{FailedPage, ParseReport, ParseWarning};
+pub use selection::RevisionSelection;
+pub use stream::{dump_parts, PageStream, RevisionStream};
+pub use visitor::{PageVisitor, Visit};
+
+use checkpoint::{MultiStreamDecoder, StreamStart};
 use parse_wiki_text::{Configuration, ConfigurationSource, Node};
-use quick_xml::events::Event;
+use pool::WorkerPool;
+use progress::{CountingReader, ProgressCallback};
 use quick_xml::reader::Reader;
 use rayon::prelude::*;
+use reader::DumpReader;
+use render::{Output, PlainTextRenderer, Renderer};
 use std::fs::File;
-use std::io::{BufRead, BufReader, Read};
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
+use std::ops::Range;
 use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 type Exception = Box<dyn std::error::Error + 'static>;
 
 /// Represents a wiki page.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Page {
     pub title: String,
+    /// The unique ID of the page, or 0 if the dump does not include it.
+    pub id: u64,
+    /// The namespace the page belongs to, e.g., 0 for articles or 1 for Talk
+    /// pages.
+    pub namespace: i32,
     pub revisions: Vec<PageRevision>,
 }
 
@@ -47,6 +102,8 @@ impl Page {
     fn new() -> Page {
         Page {
             title: "".to_string(),
+            id: 0,
+            namespace: 0,
             revisions: vec![],
         }
     }
@@ -54,16 +111,64 @@ impl Page {
     /// Reset internal data without allocating.
     fn reset(&mut self) -> &Self {
         self.title.clear();
+        self.id = 0;
+        self.namespace = 0;
         self.revisions.clear();
         self
     }
 }
 
+/// The page a revision belongs to, which is yielded along with each revision
+/// by a [RevisionStream](struct.RevisionStream.html).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PageContext {
+    pub title: String,
+    /// The unique ID of the page, or 0 if the dump does not include it.
+    pub id: u64,
+    /// The namespace the page belongs to, e.g., 0 for articles or 1 for Talk
+    /// pages.
+    pub namespace: i32,
+}
+
+impl From<&Page> for PageContext {
+    fn from(page: &Page) -> Self {
+        PageContext {
+            title: page.title.clone(),
+            id: page.id,
+            namespace: page.namespace,
+        }
+    }
+}
+
 /// Represents a specific revision of a page. This means a certain version of
 /// the page a specific time with some text contents which was created by
 /// some contributor.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PageRevision {
+    /// The unique ID of the revision, or 0 if the dump does not include it.
+    pub id: u64,
+    /// The ID of the revision which this revision was based on. This is not
+    /// present for the first revision of a page.
+    pub parent_id: Option<u64>,
+    /// The time the revision was made, in ISO 8601 format, e.g.,
+    /// "2019-04-26T03:40:24Z".
+    pub timestamp: String,
+    /// The user who made the revision.
+    pub contributor: Contributor,
+    /// The edit summary given for the revision.
+    pub comment: Comment,
+    /// The SHA-1 hash of the revision's content, in base 36.
+    pub sha1: String,
+    /// Whether the text of the revision is included in the dump. If it is
+    /// not, then `text` and `raw` are empty.
+    pub text_content: TextContent,
+    /// The size of the revision's text in bytes, as given by the dump.
+    pub text_bytes: Option<u64>,
+    /// The ID under which the revision's text is stored. This is only
+    /// included in stub dumps, which refer to the text rather than include it.
+    pub text_id: Option<u64>,
     /// The text content of the page. Depending on whether the parser is
     /// processing wiki text or not, this could either be the raw wiki text
     /// or it could be an interpreted representation.
@@ -71,44 +176,326 @@ pub struct PageRevision {
     /// The raw wiki text of the page. This is always the unprocessed, verbatim
     /// text of the page.
     pub raw: String,
+    /// The ID of the revision in which the content of the main slot was last
+    /// changed, if it is known. This is only present in dumps using export
+    /// schema 0.11 or later.
+    pub origin: Option<u64>,
+    /// The content model of the main slot, e.g., "wikitext". Only wiki text is
+    /// processed by the parser, other content (like JSON) is left as-is.
+    pub model: String,
+    /// The serialization format of the main slot, e.g., "text/x-wiki".
+    pub format: String,
+    /// The content slots of the revision besides the main slot, such as the
+    /// structured data ("mediainfo") of files on Wikimedia Commons. These are
+    /// only present in dumps using export schema 0.11 or later.
+    pub slots: Vec<RevisionSlot>,
+    /// The names of the categories the page is in, without the namespace
+    /// prefix, e.g., "Art" for `[[Category:Art]]`. This is only available if
+    /// the parser is processing wiki text.
+    pub categories: Vec<String>,
+    /// The targets of the internal links in the text, e.g., "Earth" for
+    /// `[[Earth|our planet]]`, in the order they appear. This is only
+    /// available if the parser is processing wiki text.
+    pub links: Vec<String>,
+    /// The internal links in the text, along with where their text ends up in
+    /// `text`. This is only available if the parser is processing wiki text.
+    pub link_spans: Vec<LinkSpan>,
+    /// The problems which were found while parsing the wiki text. This is
+    /// only available if the parser is processing wiki text.
+    pub warnings: Vec<ParseWarning>,
+    /// Set if the wiki text was over the processing limits of the parser, in
+    /// which case it was handled according to the parser's
+    /// [BudgetAction](enum.BudgetAction.html).
+    pub budget_exceeded: Option<BudgetExceeded>,
+    /// Maps ranges of `text` back to ranges of `raw`. This is only available
+    /// if enabled with [map_offsets](struct.Parser.html#method.map_offsets).
+    pub offsets: Option<OffsetMap>,
 }
 
 impl PageRevision {
     fn new() -> PageRevision {
         PageRevision {
+            id: 0,
+            parent_id: None,
+            timestamp: "".to_string(),
+            contributor: Contributor::Unknown,
+            comment: Comment::Empty,
+            sha1: "".to_string(),
+            text_content: TextContent::NotIncluded,
+            text_bytes: None,
+            text_id: None,
             text: "".to_string(),
             raw: "".to_string(),
+            origin: None,
+            model: "".to_string(),
+            format: "".to_string(),
+            slots: vec![],
+            categories: vec![],
+            links: vec![],
+            link_spans: vec![],
+            warnings: vec![],
+            budget_exceeded: None,
+            offsets: None,
         }
     }
 
+    /// Returns the additional content slot with the given role, e.g.,
+    /// "mediainfo", if the revision has one.
+    pub fn slot(&self, role: &str) -> Option<&RevisionSlot> {
+        self.slots.iter().find(|s| s.role == role)
+    }
+
     /// Reset internal data without allocating.
     fn reset(&mut self) -> &mut Self {
+        self.id = 0;
+        self.parent_id = None;
+        self.timestamp.clear();
+        self.contributor = Contributor::Unknown;
+        self.comment = Comment::Empty;
+        self.sha1.clear();
+        self.text_content = TextContent::NotIncluded;
+        self.text_bytes = None;
+        self.text_id = None;
         self.text.clear();
+        self.raw.clear();
+        self.origin = None;
+        self.model.clear();
+        self.format.clear();
+        self.slots.clear();
+        self.categories.clear();
+        self.links.clear();
+        self.link_spans.clear();
+        self.warnings.clear();
+        self.budget_exceeded = None;
+        self.offsets = None;
         self
     }
+
+    /// Returns true if the main slot contains wiki text. Dumps older than
+    /// export schema 0.10 do not specify a content model, in which case
+    /// everything is assumed to be wiki text.
+    fn is_wiki_text(&self) -> bool {
+        self.model.is_empty() || self.model == "wikitext"
+    }
+}
+
+/// Describes whether the text of a revision is included in a dump.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TextContent {
+    /// The text is included in the dump.
+    Included,
+    /// The text is not included in the dump. Stub dumps, like
+    /// `stub-meta-history`, only refer to the text of each revision by its ID
+    /// and size, so that metadata can be analyzed without the text.
+    NotIncluded,
+    /// The text has been deleted or suppressed by an administrator, and is
+    /// not available to the public.
+    Deleted,
+}
+
+/// The user who made a revision.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Contributor {
+    /// A registered user.
+    User {
+        /// The unique ID of the user.
+        id: u64,
+        /// The name of the user, e.g., "Jimbo Wales".
+        username: String,
+    },
+    /// An unregistered user, who is only known by their IP address.
+    Anonymous {
+        /// The IP address of the user, e.g., "198.51.100.7".
+        ip: String,
+    },
+    /// The contributor has been deleted or suppressed by an administrator,
+    /// and is not available to the public.
+    Deleted,
+    /// The dump does not include the contributor.
+    Unknown,
+}
+
+impl Contributor {
+    /// Returns the name of the user, or the IP address for unregistered
+    /// users. Returns `None` if the contributor is not available.
+    pub fn name(&self) -> Option<&str> {
+        match self {
+            Contributor::User { username, .. } => Some(username),
+            Contributor::Anonymous { ip } => Some(ip),
+            Contributor::Deleted | Contributor::Unknown => None,
+        }
+    }
+}
+
+/// The edit summary of a revision.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Comment {
+    /// The edit summary, verbatim.
+    Text(String),
+    /// The contributor did not leave an edit summary.
+    Empty,
+    /// The edit summary has been deleted or suppressed by an administrator,
+    /// and is not available to the public.
+    Deleted,
+}
+
+impl Comment {
+    /// Returns the text of the edit summary, if there is one and it is
+    /// available.
+    pub fn text(&self) -> Option<&str> {
+        match self {
+            Comment::Text(text) => Some(text),
+            Comment::Empty | Comment::Deleted => None,
+        }
+    }
+}
+
+/// Represents an additional content slot of a revision. Since MediaWiki 1.32,
+/// a revision can hold several pieces of content which are each stored in a
+/// slot with a given role. The "main" slot is the regular page content, and
+/// is represented by the revision itself.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RevisionSlot {
+    /// The role of the slot, e.g., "mediainfo".
+    pub role: String,
+    /// The ID of the revision in which the content of this slot was last
+    /// changed, if it is known.
+    pub origin: Option<u64>,
+    /// The content model of the slot, e.g., "wikibase-mediainfo".
+    pub model: String,
+    /// The serialization format of the slot, e.g., "application/json".
+    pub format: String,
+    /// The verbatim content of the slot. This is never processed.
+    pub text: String,
+}
+
+impl RevisionSlot {
+    fn new() -> RevisionSlot {
+        RevisionSlot {
+            role: "".to_string(),
+            origin: None,
+            model: "".to_string(),
+            format: "".to_string(),
+            text: "".to_string(),
+        }
+    }
+}
+
+/// An internal link in the processed text of a revision, which can be used to
+/// find mentions of other pages, e.g., for entity linking.
+///
+/// # Example
+/// ```rust
+/// use wikidump::Parser;
+///
+/// let parser = Parser::new();
+/// let site = parser.parse_file("tests/simplewiki.xml").unwrap();
+/// let revision = &site.pages[0].revisions[0];
+///
+/// for link in &revision.link_spans {
+///     let text = &revision.text[link.start..link.end];
+///     println!("{} links to {}", text, link.normalized_target);
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LinkSpan {
+    /// The byte offset in the text where the text of the link starts.
+    pub start: usize,
+    /// The byte offset in the text where the text of the link ends. This is
+    /// the same as `start` if the link has no text.
+    pub end: usize,
+    /// The target of the link as it is written, e.g., "moon_landing#Apollo 11"
+    /// for `[[moon_landing#Apollo 11|landing]]`.
+    pub target: String,
+    /// The title of the page which the link refers to, normalized the way
+    /// MediaWiki does it, e.g., "Moon landing". Any section is left out, and
+    /// the first letter is in upper case like on most wikis.
+    pub normalized_target: String,
 }
 
 /// Represents a Mediawiki website, like Wikipedia, for example.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Site {
     /// The name of the website, e.g., "Wikipedia".
     pub name: String,
+    /// The name of the wiki's database, e.g., "enwiki". Unlike the name, this
+    /// is unique to each wiki.
+    pub dbname: String,
     /// The base URL of the website, e.g., "https://en.wikipedia.org/wiki/Main_Page".
     pub url: String,
+    /// The version of MediaWiki which created the dump, e.g.,
+    /// "MediaWiki 1.34.0-wmf.17".
+    pub generator: String,
+    /// How the first letter of titles is treated, e.g., "first-letter" if
+    /// titles are always capitalized.
+    pub case: String,
+    /// The namespaces of the website.
+    pub namespaces: Vec<Namespace>,
     /// The wiki pages belonging to the website.
     pub pages: Vec<Page>,
+    /// A summary of how well the wiki text of the pages could be parsed.
+    pub report: ParseReport,
 }
 
 impl Site {
     fn new() -> Site {
         Site {
             name: "".to_string(),
+            dbname: "".to_string(),
             url: "".to_string(),
+            generator: "".to_string(),
+            case: "".to_string(),
+            namespaces: vec![],
             pages: vec![],
+            report: ParseReport::default(),
+        }
+    }
+
+    /// Checks that the siteinfo of another part of a split dump matches this
+    /// one.
+    fn check_same_site(&self, other: &Site, path: &Path) -> Result<(), Error> {
+        let fields = [
+            ("sitename", &self.name, &other.name),
+            ("dbname", &self.dbname, &other.dbname),
+            ("base", &self.url, &other.url),
+        ];
+
+        for (field, expected, found) in fields.iter() {
+            if expected != found {
+                return Err(Error::SiteInfoMismatch {
+                    path: path.to_path_buf(),
+                    field,
+                    expected: expected.to_string(),
+                    found: found.to_string(),
+                });
+            }
         }
+
+        Ok(())
     }
 }
 
+/// Represents a namespace of a website, which is a group of pages with a
+/// common purpose, like Talk pages.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Namespace {
+    /// The number of the namespace, e.g., 1 for Talk pages.
+    pub key: i32,
+    /// How the first letter of titles in the namespace is treated, e.g.,
+    /// "first-letter".
+    pub case: String,
+    /// The name of the namespace, which prefixes the titles of its pages,
+    /// e.g., "Talk". The main namespace has no name.
+    pub name: String,
+}
+
 /// A parser which can process uncompressed Mediawiki XML dumps (backups).
 pub struct Parser {
     /// If true, the wiki text will be parsed and turned into simple text which
@@ -123,7 +510,39 @@ pub struct Parser {
     /// Any ignored pages will simply be skipped by the parser.
     exclude_pages: bool,
     /// The specific wiki configuration for parsing.
-    wiki_config: Configuration,
+    wiki_config: Arc<Configuration>,
+    /// Turns the parsed wiki text into the text of each revision.
+    renderer: Arc<dyn Renderer>,
+    /// If true, then each revision gets a map from its text to its raw text.
+    map_offsets: bool,
+    /// The size in bytes above which wiki text is not processed.
+    max_text_size: Option<usize>,
+    /// The time after which processing wiki text is given up.
+    time_budget: Option<Duration>,
+    /// Runs the processing of wiki text which has a time budget.
+    render_pool: WorkerPool,
+    /// What to do with revisions which are over the limits above.
+    budget_action: BudgetAction,
+    /// If true, then pages with malformed XML are skipped rather than
+    /// ending the parse with an error.
+    lenient: bool,
+    /// If true, then the names of end tags are checked against their start
+    /// tags.
+    strict: bool,
+    /// Called with the progress of the parser every `progress_interval`.
+    progress_callback: Option<ProgressCallback>,
+    progress_interval: Duration,
+    /// Where streams of split dumps start reading, if not at the beginning.
+    resume_from: Option<Checkpoint>,
+    cancellation_token: Option<CancellationToken>,
+    /// The number of pages after which the parser stops reading.
+    max_pages: Option<u64>,
+    /// The number of bytes of XML after which the parser stops reading.
+    max_bytes: Option<u64>,
+    /// If set, then only pages with a matching title are read.
+    title_filter: Option<TitleFilter>,
+    /// Which revisions of each page are kept.
+    revision_selection: RevisionSelection,
 }
 
 impl Parser {
@@ -133,7 +552,23 @@ impl Parser {
             process_wiki_text: true,
             remove_newlines: false,
             exclude_pages: true,
-            wiki_config: Configuration::default(),
+            wiki_config: Arc::new(Configuration::default()),
+            renderer: Arc::new(PlainTextRenderer),
+            map_offsets: false,
+            max_text_size: None,
+            time_budget: None,
+            render_pool: WorkerPool::new(),
+            budget_action: BudgetAction::KeepRaw,
+            lenient: false,
+            strict: false,
+            progress_callback: None,
+            progress_interval: Duration::from_secs(1),
+            resume_from: None,
+            cancellation_token: None,
+            max_pages: None,
+            max_bytes: None,
+            title_filter: None,
+            revision_selection: RevisionSelection::All,
         }
     }
 
@@ -212,7 +647,300 @@ impl Parser {
     ///     .use_config(config::wikipedia::english());
     /// ```
     pub fn use_config(mut self, config_source: ConfigurationSource) -> Self {
-        self.wiki_config = Configuration::new(&config_source);
+        self.wiki_config = Arc::new(Configuration::new(&config_source));
+        self
+    }
+
+    /// Sets the renderer which turns processed wiki text into the text of
+    /// each revision. This will only have an effect if processing wiki text is
+    /// enabled.
+    ///
+    /// The [PlainTextRenderer](render/struct.PlainTextRenderer.html) is used
+    /// by default. See [render](render/index.html).
+    ///
+    /// # Example
+    /// ```rust
+    /// use wikidump::render::PlainTextRenderer;
+    /// use wikidump::Parser;
+    ///
+    /// let parser = Parser::new().renderer(PlainTextRenderer);
+    /// ```
+    pub fn renderer<R>(mut self, renderer: R) -> Self
+    where
+        R: Renderer + 'static,
+    {
+        self.renderer = Arc::new(renderer);
+        self
+    }
+
+    /// Sets whether the parser should keep track of where each part of the
+    /// text of a revision came from in the raw wiki text, so that ranges of
+    /// the text can be mapped back to the raw text. If enabled, then
+    /// `offsets` is set on each revision which has text.
+    ///
+    /// Mapping offsets is turned off by default, since it takes extra time and
+    /// memory.
+    ///
+    /// See [OffsetMap](struct.OffsetMap.html).
+    ///
+    /// # Example
+    /// ```rust
+    /// use wikidump::Parser;
+    ///
+    /// let parser = Parser::new().map_offsets(true);
+    /// ```
+    pub fn map_offsets(mut self, value: bool) -> Self {
+        self.map_offsets = value;
+        self
+    }
+
+    /// Sets the largest size of wiki text in bytes which the parser will
+    /// process. Larger wiki text, like pages with huge tables, is not parsed
+    /// at all, and is handled according to
+    /// [over_budget](struct.Parser.html#method.over_budget) instead.
+    ///
+    /// There is no limit by default.
+    ///
+    /// # Example
+    /// ```rust
+    /// use wikidump::Parser;
+    ///
+    /// let parser = Parser::new().max_text_size(2 * 1024 * 1024);
+    /// ```
+    pub fn max_text_size(mut self, bytes: usize) -> Self {
+        self.max_text_size = Some(bytes);
+        self
+    }
+
+    /// Sets how long the parser may spend processing the wiki text of a
+    /// single revision. Wiki text which is not done within the budget is
+    /// handled according to
+    /// [over_budget](struct.Parser.html#method.over_budget) instead, and the
+    /// parser carries on with the next revision.
+    ///
+    /// Parsing wiki text cannot be interrupted, so with a time budget the
+    /// wiki text is processed on a pool of worker threads, and the parser
+    /// stops waiting for a worker once the budget is used up. That worker is
+    /// left to finish in the background, and is counted in the
+    /// `running_over_budget` of the report until it is done. There are at
+    /// most as many such workers as rayon has threads. Beyond that, wiki text
+    /// is processed without a worker, and only checked against the budget
+    /// once it is done. Use
+    /// [max_text_size](struct.Parser.html#method.max_text_size) to avoid
+    /// parsing huge wiki text in the first place.
+    ///
+    /// There is no time budget by default.
+    ///
+    /// # Example
+    /// ```rust
+    /// use std::time::Duration;
+    /// use wikidump::Parser;
+    ///
+    /// let parser = Parser::new().time_budget(Duration::from_secs(1));
+    /// ```
+    pub fn time_budget(mut self, budget: Duration) -> Self {
+        self.time_budget = Some(budget);
+        self
+    }
+
+    /// Sets what the parser does with revisions whose wiki text is over the
+    /// processing limits. Every such revision has its `budget_exceeded` set,
+    /// and is listed in the report of the site.
+    ///
+    /// The raw wiki text is kept by default.
+    ///
+    /// # Example
+    /// ```rust
+    /// use wikidump::{BudgetAction, Parser};
+    ///
+    /// let parser = Parser::new()
+    ///     .max_text_size(2 * 1024 * 1024)
+    ///     .over_budget(BudgetAction::Skip);
+    /// ```
+    pub fn over_budget(mut self, action: BudgetAction) -> Self {
+        self.budget_action = action;
+        self
+    }
+
+    /// Sets whether the parser should skip pages which cannot be read
+    /// because the XML of the dump is malformed, e.g., because the dump was
+    /// truncated. Reading carries on from the next `<page>`, and each
+    /// skipped page is listed in the `failed_pages` of the report.
+    ///
+    /// This is disabled by default, so malformed XML ends the parse with an
+    /// [Error::Xml](enum.Error.html#variant.Xml).
+    ///
+    /// # Example
+    /// ```rust
+    /// use wikidump::Parser;
+    ///
+    /// let parser = Parser::new().lenient(true);
+    /// let site = parser.parse_file("tests/simplewiki.xml").unwrap();
+    ///
+    /// for page in &site.report.failed_pages {
+    ///     println!("Skipped {:?} at byte {}", page.title, page.position);
+    /// }
+    /// ```
+    pub fn lenient(mut self, value: bool) -> Self {
+        self.lenient = value;
+        self
+    }
+
+    /// Sets whether the parser should check that every end tag matches its
+    /// start tag. This is slower, so it is best kept for dumps which are
+    /// expected to be well formed, like test fixtures.
+    ///
+    /// End tags are not checked by default.
+    ///
+    /// # Example
+    /// ```rust
+    /// use wikidump::Parser;
+    ///
+    /// let parser = Parser::new().strict(true);
+    /// let site = parser.parse_file("tests/simplewiki.xml").unwrap();
+    /// ```
+    pub fn strict(mut self, value: bool) -> Self {
+        self.strict = value;
+        self
+    }
+
+    /// Sets a callback which is called with the progress of the parser while
+    /// it reads a dump, e.g., to show a progress bar. The callback is called
+    /// after a page has been read, at most once every
+    /// [progress_interval](struct.Parser.html#method.progress_interval), and
+    /// once more when the end of each dump file has been reached.
+    ///
+    /// Like the renderer, the callback must be `Send` and `Sync`. To receive
+    /// the progress on another thread, send it through a channel.
+    ///
+    /// # Example
+    /// ```rust
+    /// use std::sync::mpsc;
+    /// use wikidump::Parser;
+    ///
+    /// let (sender, receiver) = mpsc::channel();
+    /// let parser = Parser::new().on_progress(move |progress| {
+    ///     sender.send(progress.clone()).unwrap();
+    /// });
+    /// let site = parser.parse_file("tests/simplewiki.xml").unwrap();
+    ///
+    /// let last = receiver.try_iter().last().unwrap();
+    /// assert_eq!(last.pages_kept, site.pages.len() as u64);
+    /// assert_eq!(last.fraction(), Some(1.0));
+    /// ```
+    pub fn on_progress<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(&Progress) + Send + Sync + 'static,
+    {
+        self.progress_callback = Some(Box::new(callback));
+        self
+    }
+
+    /// Sets how long the parser waits between calls to the callback set with
+    /// [on_progress](struct.Parser.html#method.on_progress).
+    ///
+    /// The default interval is one second.
+    pub fn progress_interval(mut self, interval: Duration) -> Self {
+        self.progress_interval = interval;
+        self
+    }
+
+    /// Sets a checkpoint from which
+    /// [stream_files](struct.Parser.html#method.stream_files) and
+    /// [parse_files](struct.Parser.html#method.parse_files) carry on reading,
+    /// rather than starting at the beginning of the dump. The pages up to
+    /// and including the last page of the checkpoint are not read again.
+    ///
+    /// The same parts must be given as when the checkpoint was taken. See
+    /// [Checkpoint](struct.Checkpoint.html).
+    pub fn resume_from(mut self, checkpoint: Checkpoint) -> Self {
+        self.resume_from = Some(checkpoint);
+        self
+    }
+
+    /// Sets a token which cancels the parser once it is cancelled. Reading
+    /// stops at the next page or revision, and processing stops at the next
+    /// revision, after which an
+    /// [Error::Cancelled](enum.Error.html#variant.Cancelled) is returned.
+    ///
+    /// See [CancellationToken](struct.CancellationToken.html).
+    pub fn cancellation_token(mut self, token: CancellationToken) -> Self {
+        self.cancellation_token = Some(token);
+        self
+    }
+
+    /// Sets the number of pages after which the parser stops reading the
+    /// dump, as if it had ended there. Pages which are excluded or skipped
+    /// are not counted.
+    ///
+    /// There is no limit by default.
+    ///
+    /// # Example
+    /// ```rust
+    /// use wikidump::Parser;
+    ///
+    /// let parser = Parser::new().max_pages(3);
+    /// let site = parser.parse_file("tests/simplewiki.xml").unwrap();
+    /// assert_eq!(site.pages.len(), 3);
+    /// ```
+    pub fn max_pages(mut self, pages: u64) -> Self {
+        self.max_pages = Some(pages);
+        self
+    }
+
+    /// Sets a filter which decides which pages are read by their title. Pages
+    /// which do not match are skipped as soon as their title has been read,
+    /// the same way as pages which are not articles, so little time is spent
+    /// on them.
+    ///
+    /// See [TitleFilter](struct.TitleFilter.html).
+    ///
+    /// # Example
+    /// ```rust
+    /// use wikidump::{Parser, TitleFilter};
+    ///
+    /// let parser = Parser::new().filter_titles(TitleFilter::prefix("A"));
+    /// let site = parser.parse_file("tests/simplewiki.xml").unwrap();
+    ///
+    /// assert!(site.pages.iter().all(|p| p.title.starts_with('A')));
+    /// ```
+    pub fn filter_titles(mut self, filter: TitleFilter) -> Self {
+        self.title_filter = Some(filter);
+        self
+    }
+
+    /// Sets the number of bytes of (uncompressed) XML after which the parser
+    /// stops reading the dump. The page which is being read when the limit
+    /// is reached is still read to its end.
+    ///
+    /// There is no limit by default.
+    pub fn max_bytes(mut self, bytes: u64) -> Self {
+        self.max_bytes = Some(bytes);
+        self
+    }
+
+    /// Sets which revisions of each page are kept, which is useful for dumps
+    /// with the full history of each page. The text of revisions which are
+    /// not selected is skipped rather than stored.
+    ///
+    /// Every revision is kept by default.
+    ///
+    /// See [RevisionSelection](enum.RevisionSelection.html).
+    ///
+    /// # Example
+    /// ```rust
+    /// use wikidump::{Parser, RevisionSelection};
+    ///
+    /// let parser = Parser::new()
+    ///     .exclude_pages(false)
+    ///     .select_revisions(RevisionSelection::between(Some("2004-03"), None));
+    /// let site = parser.parse_file("tests/simplewiki-history.xml").unwrap();
+    ///
+    /// let revisions = &site.pages[0].revisions;
+    /// assert!(revisions.iter().all(|r| r.timestamp.as_str() >= "2004-03"));
+    /// ```
+    pub fn select_revisions(mut self, selection: RevisionSelection) -> Self {
+        self.revision_selection = selection;
         self
     }
 
@@ -231,17 +959,117 @@ impl Parser {
     where
         P: AsRef<Path>,
     {
-        if is_compressed(&dump) {
-            let file = File::open(dump)?;
-            let reader = BufReader::new(MultiBzDecoder::new(file));
-            let reader = Reader::from_reader(reader);
+        self.parse(open_dump(dump.as_ref(), self.strict, None)?)
+    }
 
-            self.parse(reader)
-        } else {
-            let reader = Reader::from_file(dump).expect("Could not create XML reader from file");
+    /// Returns all of the parsed data contained in a dump which has been split
+    /// into several part files, like the `pages-articles1.xml-p1p41242.bz2`
+    /// files of large wikis. The parts are read in the given order, and their
+    /// pages are combined as if they came from a single dump file.
+    ///
+    /// Every part must describe the same site, otherwise an
+    /// [Error::SiteInfoMismatch](enum.Error.html#variant.SiteInfoMismatch) is
+    /// returned. See [dump_parts](fn.dump_parts.html) for finding the parts of
+    /// a dump with a glob pattern.
+    ///
+    /// # Example
+    /// ```rust
+    /// use wikidump::Parser;
+    ///
+    /// let parser = Parser::new();
+    /// let site = parser.parse_files([
+    ///     "tests/parts/simplewiki-pages-articles1.xml-p1p6",
+    ///     "tests/parts/simplewiki-pages-articles2.xml-p8p12.bz2",
+    /// ]);
+    /// ```
+    pub fn parse_files<I, P>(&self, parts: I) -> Result<Site, Exception>
+    where
+        I: IntoIterator<Item = P>,
+        P: AsRef<Path>,
+    {
+        let mut stream = PageStream::new(self, parts, false)?;
+        let mut pages = vec![];
 
-            self.parse(reader)
+        for page in &mut stream {
+            pages.push(page?);
         }
+
+        self.process_pages(&mut pages);
+        self.check_cancelled()?;
+
+        let mut site = stream.site().clone();
+        site.pages = pages;
+        site.report = stream.report().clone();
+        self.add_to_report(&mut site);
+        Ok(site)
+    }
+
+    /// Returns a stream of the pages contained in a dump which has been split
+    /// into several part files. Unlike
+    /// [parse_files](struct.Parser.html#method.parse_files), pages are read
+    /// and processed one at a time as the stream is iterated over, so memory
+    /// usage stays low regardless of the size of the dump.
+    ///
+    /// The site information of the first part is read immediately, and each
+    /// following part is checked against it as it is opened.
+    ///
+    /// # Example
+    /// ```rust
+    /// use wikidump::Parser;
+    ///
+    /// let parser = Parser::new();
+    /// let stream = parser
+    ///     .stream_files([
+    ///         "tests/parts/simplewiki-pages-articles1.xml-p1p6",
+    ///         "tests/parts/simplewiki-pages-articles2.xml-p8p12.bz2",
+    ///     ])
+    ///     .unwrap();
+    ///
+    /// assert_eq!(stream.site().dbname, "simplewiki");
+    ///
+    /// for page in stream {
+    ///     println!("{}", page.unwrap().title);
+    /// }
+    /// ```
+    pub fn stream_files<I, P>(&self, parts: I) -> Result<PageStream<'_>, Exception>
+    where
+        I: IntoIterator<Item = P>,
+        P: AsRef<Path>,
+    {
+        PageStream::new(self, parts, true)
+    }
+
+    /// Returns a stream of the revisions contained in a dump, which may be
+    /// split into several part files like for
+    /// [stream_files](struct.Parser.html#method.stream_files). Each revision
+    /// is read and processed on its own, and yielded along with the title, ID
+    /// and namespace of its page, so memory usage stays low even for pages
+    /// with hundreds of thousands of revisions in history dumps.
+    ///
+    /// Only the revisions selected with
+    /// [select_revisions](struct.Parser.html#method.select_revisions) are
+    /// yielded.
+    ///
+    /// # Example
+    /// ```rust
+    /// use wikidump::Parser;
+    ///
+    /// let parser = Parser::new().exclude_pages(false);
+    /// let stream = parser
+    ///     .stream_revisions(["tests/simplewiki-history.xml"])
+    ///     .unwrap();
+    ///
+    /// for result in stream {
+    ///     let (page, revision) = result.unwrap();
+    ///     println!("{} {}", page.title, revision.timestamp);
+    /// }
+    /// ```
+    pub fn stream_revisions<I, P>(&self, parts: I) -> Result<RevisionStream<'_>, Exception>
+    where
+        I: IntoIterator<Item = P>,
+        P: AsRef<Path>,
+    {
+        RevisionStream::new(self, parts)
     }
 
     /// Returns all of the parsed data contained in a particular wiki dump file.
@@ -260,199 +1088,452 @@ impl Parser {
     pub fn parse_str(&self, text: &str) -> Result<Site, Exception> {
         let reader = Reader::from_str(text);
 
-        self.parse(reader)
+        self.parse(DumpReader::new(reader, self.strict))
+    }
+
+    /// Reads a dump file and passes its contents to a visitor as they are
+    /// read, rather than collecting every page into a `Site`. Each revision
+    /// is processed right before it is passed to the visitor, and the
+    /// visitor can skip pages or stop reading the dump early.
+    ///
+    /// See [PageVisitor](trait.PageVisitor.html).
+    ///
+    /// # Example
+    /// ```rust
+    /// use wikidump::{Page, PageVisitor, Parser, Visit};
+    ///
+    /// struct LongestPage(String, usize);
+    ///
+    /// impl PageVisitor for LongestPage {
+    ///     fn on_page_end(&mut self, page: &Page) -> Visit {
+    ///         let length = page.revisions.iter().map(|r| r.text.len()).sum();
+    ///
+    ///         if length > self.1 {
+    ///             self.0 = page.title.clone();
+    ///             self.1 = length;
+    ///         }
+    ///
+    ///         Visit::Continue
+    ///     }
+    /// }
+    ///
+    /// let mut longest = LongestPage(String::new(), 0);
+    /// Parser::new()
+    ///     .visit_file("tests/simplewiki.xml", &mut longest)
+    ///     .unwrap();
+    /// ```
+    pub fn visit_file<P, V>(&self, dump: P, visitor: &mut V) -> Result<(), Exception>
+    where
+        P: AsRef<Path>,
+        V: PageVisitor,
+    {
+        self.visit(open_dump(dump.as_ref(), self.strict, None)?, visitor)
+    }
+
+    /// Reads a dump from a string and passes its contents to a visitor, like
+    /// [visit_file](struct.Parser.html#method.visit_file).
+    pub fn visit_str<V>(&self, text: &str, visitor: &mut V) -> Result<(), Exception>
+    where
+        V: PageVisitor,
+    {
+        self.visit(
+            DumpReader::new(Reader::from_str(text), self.strict),
+            visitor,
+        )
     }
 
-    fn parse<R>(&self, mut reader: Reader<R>) -> Result<Site, Exception>
+    fn visit<R>(
+        &self,
+        mut reader: DumpReader<R>,
+        visitor: &mut dyn PageVisitor,
+    ) -> Result<(), Exception>
     where
         R: BufRead,
     {
-        let config = reader.config_mut();
-        // Save time by assuming well formed XML is passed in.
-        config.check_end_names = false;
-        config.trim_markup_names_in_closing_tags = false;
+        let mut site = Site::new();
+        let mut current_page = Page::new();
+
+        reader.read_site_info(&mut site)?;
+
+        if visitor.on_siteinfo(&site) == Visit::Stop {
+            return Ok(());
+        }
 
+        while reader.next_page(
+            self,
+            &mut current_page,
+            Some(&mut *visitor),
+            &mut site.report,
+        )? {
+            if visitor.on_page_end(&current_page) == Visit::Stop {
+                break;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn parse<R>(&self, mut reader: DumpReader<R>) -> Result<Site, Exception>
+    where
+        R: BufRead,
+    {
         let mut site = Site::new();
-        let mut buf = Vec::new();
-        let mut text_buf = Vec::new();
         let mut current_page = Page::new();
-        let mut current_page_revision = PageRevision::new();
-        let mut skipping_current_page = false;
-
-        loop {
-            match reader.read_event_into(&mut buf) {
-                Ok(Event::Start(ref e)) => {
-                    if skipping_current_page {
-                        continue;
-                    }
 
-                    match e.name().as_ref() {
-                        b"sitename" => {
-                            // Read text event
-                            site.name = get_text_from_event(&mut reader, &mut text_buf)
-                        }
-                        b"base" => site.url = get_text_from_event(&mut reader, &mut text_buf),
-                        b"text" => {
-                            current_page_revision.text =
-                                get_text_from_event(&mut reader, &mut text_buf)
-                        }
-                        b"title" => {
-                            current_page.title = get_text_from_event(&mut reader, &mut text_buf)
-                        }
-                        b"ns" => {
-                            if self.exclude_pages {
-                                let ns = get_text_from_event(&mut reader, &mut text_buf);
-
-                                if ns != "0" {
-                                    // Skip this page
-                                    skipping_current_page = true;
-                                    continue;
-                                }
-                            }
-                        }
-                        _ => {}
-                    };
-                }
-                Ok(Event::End(ref e)) => {
-                    match e.name().as_ref() {
-                        b"page" => {
-                            if !skipping_current_page {
-                                site.pages.push(current_page.clone());
-                                current_page.reset();
-                            }
-
-                            skipping_current_page = false;
-                        }
-                        b"revision" => {
-                            current_page.revisions.push(current_page_revision.clone());
-                            current_page_revision.reset();
-                        }
-                        _ => {}
-                    };
-                }
-                Ok(Event::Eof) => break, // exits the loop when reaching end of file
-                Err(e) => panic!("Error at position {}: {:?}", reader.buffer_position(), e),
-                _ => (), // There are several other `Event`s we do not consider here
+        reader.read_site_info(&mut site)?;
+
+        while reader.next_page(self, &mut current_page, None, &mut site.report)? {
+            site.pages.push(current_page.clone());
+        }
+
+        self.process_pages(&mut site.pages);
+        self.check_cancelled()?;
+        self.add_to_report(&mut site);
+
+        Ok(site)
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancellation_token
+            .as_ref()
+            .is_some_and(CancellationToken::is_cancelled)
+    }
+
+    /// Returns an error if the parser has been cancelled.
+    fn check_cancelled(&self) -> Result<(), Error> {
+        if self.is_cancelled() {
+            Err(Error::Cancelled)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Adds the processed pages of a site to its report.
+    fn add_to_report(&self, site: &mut Site) {
+        for page in &site.pages {
+            site.report.add_page(page, self.process_wiki_text);
+        }
+
+        self.update_report(&mut site.report);
+    }
+
+    /// Processes the wiki text of many pages at once, in parallel.
+    fn process_pages(&self, pages: &mut [Page]) {
+        pages.par_iter_mut().for_each(|p: &mut Page| {
+            p.revisions
+                .par_iter_mut()
+                .for_each(|r: &mut PageRevision| self.process_revision(r))
+        });
+    }
+
+    /// Processes the wiki text of a single page.
+    fn process_page(&self, page: &mut Page) {
+        page.revisions
+            .iter_mut()
+            .for_each(|r: &mut PageRevision| self.process_revision(r));
+    }
+
+    fn process_revision(&self, r: &mut PageRevision) {
+        if r.text_content != TextContent::Included || self.is_cancelled() {
+            return;
+        }
+
+        r.budget_exceeded = None;
+
+        if !(self.process_wiki_text && r.is_wiki_text() && self.render_wiki_text(r)) {
+            if r.budget_exceeded.is_some() && self.budget_action == BudgetAction::Skip {
+                r.text.clear();
+                r.offsets = None;
+                return;
             }
 
-            // if we don't keep a borrow elsewhere, we can clear the buffer to keep memory usage low
-            buf.clear();
-            text_buf.clear();
+            r.text = r.raw.as_str().to_string();
+            r.offsets = Some(OffsetMap::identity(r.text.len())).filter(|_| self.map_offsets);
+            r.link_spans.clear();
+            r.warnings.clear();
         }
 
-        site.pages.par_iter_mut().for_each(|p: &mut Page| {
-            p.revisions.par_iter_mut().for_each(|r: &mut PageRevision| {
-                if self.process_wiki_text {
-                    let parsed_output = self.wiki_config.parse(r.text.as_str());
+        if self.remove_newlines {
+            remove_text(r, |text| {
+                text.match_indices(['\n', '\r'])
+                    .map(|(i, m)| i..i + m.len())
+                    .collect()
+            });
+        }
 
-                    r.raw = r.text.as_str().to_string();
-                    r.text = get_text_from_nodes(&parsed_output.nodes).replace("\\t", "");
-                }
+        remove_text(r, |text| {
+            let start = text.len() - text.trim_start().len();
+            let end = text.trim_end().len().max(start);
+            vec![0..start, end..text.len()]
+        });
+    }
+
+    /// Parses and renders the wiki text of a revision, unless it is over the
+    /// processing limits. Returns false if it is.
+    fn render_wiki_text(&self, r: &mut PageRevision) -> bool {
+        if self.max_text_size.is_some_and(|max| r.raw.len() > max) {
+            r.budget_exceeded = Some(BudgetExceeded::TextSize { bytes: r.raw.len() });
+            return false;
+        }
 
-                if self.remove_newlines {
-                    r.text = r.text.replace("\n", "");
-                    r.text = r.text.replace("\r", "");
+        let rendered = match self.time_budget {
+            Some(budget) => {
+                let started = Instant::now();
+
+                match self.render_within(budget, r.raw.clone()) {
+                    Some(rendered) if started.elapsed() <= budget => rendered,
+                    _ => {
+                        let elapsed = started.elapsed();
+                        r.budget_exceeded = Some(BudgetExceeded::Time { elapsed });
+                        return false;
+                    }
                 }
+            }
+            None => render(
+                &self.wiki_config,
+                self.renderer.as_ref(),
+                r.raw.as_str(),
+                self.map_offsets,
+            ),
+        };
 
-                r.text = r.text.trim().to_string();
-            })
+        r.text = rendered.text;
+        r.offsets = rendered.offsets;
+        r.link_spans = rendered.link_spans;
+        r.categories = rendered.categories;
+        r.links = rendered.links;
+        r.warnings = rendered.warnings;
+        remove_text(r, |text| {
+            text.match_indices("\\t")
+                .map(|(i, m)| i..i + m.len())
+                .collect()
         });
 
-        Ok(site)
+        true
+    }
+
+    /// Renders wiki text on a worker of the pool, and returns `None` if it is
+    /// not done within the budget.
+    fn render_within(&self, budget: Duration, raw: String) -> Option<Rendered> {
+        let config = Arc::clone(&self.wiki_config);
+        let renderer = Arc::clone(&self.renderer);
+        let map_offsets = self.map_offsets;
+        let job = move || render(&config, renderer.as_ref(), &raw, map_offsets);
+
+        match self.render_pool.run(budget, job) {
+            Ok(rendered) => rendered,
+            // Too many workers are still busy with wiki text which was over
+            // budget, so this wiki text is rendered here instead, and only
+            // checked against the budget once it is done.
+            Err(job) => Some(job()),
+        }
+    }
+
+    /// Brings the parts of a report up to date which do not depend on the
+    /// pages which have been read.
+    fn update_report(&self, report: &mut ParseReport) {
+        report.running_over_budget = self.render_pool.abandoned();
+    }
+}
+
+/// The result of parsing and rendering the wiki text of a revision.
+struct Rendered {
+    text: String,
+    offsets: Option<OffsetMap>,
+    link_spans: Vec<LinkSpan>,
+    categories: Vec<String>,
+    links: Vec<String>,
+    warnings: Vec<ParseWarning>,
+}
+
+/// Parses and renders wiki text.
+fn render(
+    config: &Configuration,
+    renderer: &dyn Renderer,
+    raw: &str,
+    map_offsets: bool,
+) -> Rendered {
+    let parsed_output = config.parse(raw);
+
+    // 64 is just a guess here, not really well benchmarked or anything
+    let capacity = 64 + 64 * parsed_output.nodes.len();
+    let mut output = Output::new(capacity, map_offsets);
+    renderer.render_nodes(&parsed_output.nodes, &mut output);
+
+    let (text, offsets, link_spans) = output.into_parts();
+    let mut links = vec![];
+    get_links_from_nodes(&parsed_output.nodes, &mut links);
+
+    Rendered {
+        text,
+        offsets,
+        link_spans,
+        categories: get_categories_from_nodes(&parsed_output.nodes),
+        links,
+        warnings: parsed_output
+            .warnings
+            .iter()
+            .map(ParseWarning::from)
+            .collect(),
     }
 }
 
-/// Given a reader which has just read a start event, this function will read
-/// the text event which follows it and return the text as a string. This is
-/// useful for quickly reading the text of a simple element.
-fn get_text_from_event<R>(reader: &mut Reader<R>, text_buf: &mut Vec<u8>) -> String
+/// Removes the ranges of the text of a revision which are found by `find`,
+/// keeping its offset map and link spans in line with it.
+fn remove_text<F>(r: &mut PageRevision, find: F)
 where
-    R: BufRead,
+    F: Fn(&str) -> Vec<Range<usize>>,
 {
-    let event = reader.read_event_into(text_buf).unwrap();
-    match event {
-        Event::Text(e) => e.unescape().unwrap().into_owned(),
-        // There was nothing in the text, so just return an empty string
-        Event::End(_) => "".to_string(),
-        _ => panic!(
-            "Unexpected event type (expected text event, got {:?})",
-            event
-        ),
+    let mut removed = find(&r.text);
+    removed.retain(|r| !r.is_empty());
+
+    if removed.is_empty() {
+        return;
+    }
+
+    offsets::remove_ranges(&mut r.text, &removed);
+
+    if let Some(offsets) = r.offsets.as_mut() {
+        offsets.remove(&removed);
+    }
+
+    let positions = r
+        .link_spans
+        .iter_mut()
+        .flat_map(|l| [&mut l.start, &mut l.end]);
+    offsets::shift_positions(&removed, positions);
+}
+
+/// Normalizes the target of a link into the title of a page, like
+/// MediaWiki does.
+fn normalize_title(target: &str) -> String {
+    let title = target.split('#').next().unwrap_or_default();
+    let title = title.trim().trim_start_matches(':').replace('_', " ");
+    let mut words = title.split_whitespace();
+    let mut normalized = String::with_capacity(title.len());
+
+    if let Some(first) = words.next() {
+        let mut chars = first.chars();
+        normalized.extend(chars.next().into_iter().flat_map(char::to_uppercase));
+        normalized.push_str(chars.as_str());
+    }
+
+    for word in words {
+        normalized.push(' ');
+        normalized.push_str(word);
+    }
+
+    normalized
+}
+
+/// Opens a dump file for reading, decompressing it if needed. If there is a
+/// checkpoint, reading starts from the page which follows it.
+fn open_dump(
+    dump: &Path,
+    strict: bool,
+    resume: Option<&Checkpoint>,
+) -> Result<DumpReader<Box<dyn BufRead + Send>>, Exception> {
+    let stream = match resume {
+        Some(checkpoint) if checkpoint.stream_position <= checkpoint.position => StreamStart {
+            offset: checkpoint.stream_offset,
+            position: checkpoint.stream_position,
+        },
+        Some(_) => return Err(Error::InvalidCheckpoint.into()),
+        None => StreamStart {
+            offset: 0,
+            position: 0,
+        },
+    };
+
+    let mut file = File::open(dump)?;
+    let file_size = file.metadata()?.len();
+    file.seek(SeekFrom::Start(stream.offset))?;
+    let file = CountingReader::new(file);
+    let file_bytes_read = file.count();
+    let mut streams = None;
+
+    let mut reader: Box<dyn BufRead + Send> = if is_compressed(dump)? {
+        let decoder = MultiStreamDecoder::new(BufReader::new(file), stream);
+        streams = Some(decoder.starts());
+        Box::new(BufReader::new(decoder))
+    } else {
+        Box::new(BufReader::new(file))
+    };
+
+    if let Some(checkpoint) = resume {
+        // Skip to the checkpoint within its stream, and stand in for the
+        // start of the dump so that end tags can still be checked.
+        let skip = checkpoint.position - stream.position;
+        if std::io::copy(&mut reader.by_ref().take(skip), &mut std::io::sink())? < skip {
+            return Err(Error::InvalidCheckpoint.into());
+        }
+
+        reader = Box::new(RESUME_PREFIX.chain(reader));
+    }
+
+    let mut reader = DumpReader::new(Reader::from_reader(reader), strict);
+    reader.progress.set_file(file_bytes_read, file_size);
+    reader.streams = streams;
+
+    if let Some(checkpoint) = resume {
+        reader.progress.skip_file_bytes(stream.offset);
+        reader.resume(checkpoint.position, RESUME_PREFIX.len() as u64)?;
     }
+
+    Ok(reader)
 }
 
+/// The XML which is read before the rest of a dump when resuming from a
+/// checkpoint, in place of the start of the dump.
+const RESUME_PREFIX: &[u8] = b"<mediawiki>";
+
 impl Default for Parser {
     fn default() -> Self {
         Self::new()
     }
 }
 
-// TODO: document
-fn get_text_from_nodes(nodes: &[Node]) -> String {
-    // 32 is just a guess here, not really well benchmarked or anything
-    let mut node_text = String::with_capacity(64 + 64 * nodes.len());
-
-    nodes.iter().for_each(|node| {
-        match node {
-            Node::Text { value, .. } => node_text.push_str(value),
-            Node::ParagraphBreak { .. } => node_text.push('\n'),
-            Node::CharacterEntity { character, .. } => {
-                node_text.push_str(character.to_string().as_str())
-            }
-            Node::Link { text, .. } => node_text.push_str(get_text_from_nodes(text).as_str()),
-            Node::ExternalLink { nodes, .. } => {
-                node_text.push_str(get_text_from_nodes(nodes).as_str())
-            }
-            Node::Heading { nodes, .. } => {
-                node_text.push('\n');
-                node_text.push_str(get_text_from_nodes(nodes).as_str());
-                node_text.push('\n');
-            }
-            Node::Image { .. } => {
-                // @TODO @Completeness: Allow image text.
-                // Currently not allowed because it's a bit difficult to figure
-                // out what is normal text and what isn't.
-            }
-            Node::OrderedList { items, .. } | Node::UnorderedList { items, .. } => {
-                items.iter().for_each(|i| {
-                    node_text.push_str(get_text_from_nodes(&i.nodes).as_str());
-                });
-            }
-            Node::DefinitionList { items, .. } => {
-                items.iter().for_each(|i| {
-                    node_text.push_str(get_text_from_nodes(&i.nodes).as_str());
-                });
-            }
-            Node::Preformatted { nodes, .. } => {
-                node_text.push_str(get_text_from_nodes(nodes).as_str())
+/// Returns the names of the categories which the given nodes place a page
+/// in. Only top level nodes are considered, since that is where category links
+/// are placed in practice.
+fn get_categories_from_nodes(nodes: &[Node]) -> Vec<String> {
+    nodes
+        .iter()
+        .filter_map(|node| match node {
+            Node::Category { target, .. } => {
+                let name = target.split_once(':').map_or(*target, |(_, name)| name);
+
+                Some(name.trim().to_string())
             }
-            Node::Template { .. }
-            | Node::Bold { .. }
-            | Node::BoldItalic { .. }
-            | Node::HorizontalDivider { .. }
-            | Node::MagicWord { .. }
-            | Node::Italic { .. }
-            | Node::Redirect { .. }
-            | Node::Comment { .. }
-            | Node::Tag { .. }
-            | Node::StartTag { .. }
-            | Node::EndTag { .. }
-            | Node::Parameter { .. }
-            | Node::Category { .. }
-            | Node::Table { .. } => {}
+            _ => None,
+        })
+        .collect()
+}
+
+/// Collects the targets of the internal links in the given nodes. Like the
+/// text, links inside of templates, tables and images are not included.
+fn get_links_from_nodes(nodes: &[Node], links: &mut Vec<String>) {
+    nodes.iter().for_each(|node| match node {
+        Node::Link { target, text, .. } => {
+            links.push(target.trim().to_string());
+            get_links_from_nodes(text, links);
         }
+        Node::ExternalLink { nodes, .. }
+        | Node::Heading { nodes, .. }
+        | Node::Preformatted { nodes, .. } => get_links_from_nodes(nodes, links),
+        Node::OrderedList { items, .. } | Node::UnorderedList { items, .. } => items
+            .iter()
+            .for_each(|i| get_links_from_nodes(&i.nodes, links)),
+        Node::DefinitionList { items, .. } => items
+            .iter()
+            .for_each(|i| get_links_from_nodes(&i.nodes, links)),
+        _ => {}
     });
-
-    node_text
 }
 
-fn is_compressed<P>(dump: &P) -> bool
-where
-    P: AsRef<Path>,
-{
-    let bytes_to_read = 3;
-    let mut buf = vec![0u8; bytes_to_read];
-    let mut file = File::open(dump).expect("Could not open dump file");
-    file.read_exact(&mut buf).expect("Could not read dump file");
-    buf == b"BZh"
+/// Returns true if a dump file starts with the magic bytes of bzip2.
+fn is_compressed(dump: &Path) -> Result<bool, std::io::Error> {
+    let mut buf = Vec::with_capacity(3);
+    File::open(dump)?.take(3).read_to_end(&mut buf)?;
+    Ok(buf == b"BZh")
 }
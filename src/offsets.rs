@@ -0,0 +1,202 @@
+use std::ops::Range;
+
+/// Maps byte ranges in the processed `text` of a revision back to the ranges
+/// of `raw` wiki text they were rendered from. This is only available when
+/// enabled with [map_offsets](struct.Parser.html#method.map_offsets).
+///
+/// Text which was copied from the wiki text as-is, like the words of a
+/// paragraph, maps to exactly the same words in the wiki text. Text which was
+/// produced some other way, like a character entity or the newline which
+/// follows a heading, maps to the whole wiki text of the node which produced
+/// it.
+///
+/// # Example
+/// ```rust
+/// use wikidump::Parser;
+///
+/// let parser = Parser::new().map_offsets(true);
+/// let site = parser.parse_file("tests/simplewiki.xml").unwrap();
+///
+/// let revision = &site.pages[0].revisions[0];
+/// let offsets = revision.offsets.as_ref().unwrap();
+///
+/// let start = revision.text.find("April").unwrap();
+/// let raw = offsets.to_raw(start..start + 5).unwrap();
+/// assert_eq!(&revision.raw[raw], "April");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OffsetMap {
+    /// Segments of the text in order, which together cover all of the text.
+    segments: Vec<Segment>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct Segment {
+    text: Range<usize>,
+    raw: Range<usize>,
+    /// True if the text is a copy of the raw text, so that each byte maps to
+    /// the byte at the same position in the raw text.
+    exact: bool,
+}
+
+impl OffsetMap {
+    /// Maps all of a text to the same raw text, for text which is not
+    /// processed.
+    pub(crate) fn identity(len: usize) -> OffsetMap {
+        let mut map = OffsetMap::default();
+        map.push(0..len, 0..len, true);
+        map
+    }
+
+    /// Returns the range of raw wiki text which the given range of text was
+    /// rendered from, or `None` if the range is not within the text.
+    pub fn to_raw(&self, text: Range<usize>) -> Option<Range<usize>> {
+        if text.start > text.end || text.end > self.text_len() {
+            return None;
+        }
+
+        // An empty range maps to the position of the byte it comes before.
+        let end = text.end.max(text.start + 1);
+        let first = self.segments.partition_point(|s| s.text.end <= text.start);
+        let overlapping = self.segments[first..]
+            .iter()
+            .take_while(|s| s.text.start < end);
+
+        let mut raw: Option<Range<usize>> = None;
+        for segment in overlapping {
+            let mapped = if segment.exact {
+                let start = text.start.max(segment.text.start) - segment.text.start;
+                let end = text.end.min(segment.text.end) - segment.text.start;
+                segment.raw.start + start..segment.raw.start + end
+            } else {
+                segment.raw.clone()
+            };
+
+            raw = Some(match raw {
+                Some(raw) => raw.start.min(mapped.start)..raw.end.max(mapped.end),
+                None => mapped,
+            });
+        }
+
+        raw.or_else(|| {
+            // The end of the text maps to the end of the raw text.
+            let end = self.segments.last().map_or(0, |s| s.raw.end);
+            Some(end..end).filter(|_| text.is_empty())
+        })
+    }
+
+    /// Returns the length of the text which is mapped.
+    fn text_len(&self) -> usize {
+        self.segments.last().map_or(0, |s| s.text.end)
+    }
+
+    /// Adds a segment to the end of the text, merging it into the previous
+    /// segment where possible.
+    pub(crate) fn push(&mut self, text: Range<usize>, raw: Range<usize>, exact: bool) {
+        if text.is_empty() {
+            return;
+        }
+
+        if let Some(last) = self.segments.last_mut() {
+            let continues_exactly = exact && last.exact && last.raw.end == raw.start;
+            let same_node = !exact && !last.exact && last.raw == raw;
+
+            if last.text.end == text.start && (continues_exactly || same_node) {
+                last.text.end = text.end;
+                last.raw.end = raw.end;
+                return;
+            }
+        }
+
+        self.segments.push(Segment { text, raw, exact });
+    }
+
+    /// Removes the given ranges of text, which must be sorted and not
+    /// overlap, from the map.
+    pub(crate) fn remove(&mut self, removed: &[Range<usize>]) {
+        let segments = std::mem::take(&mut self.segments);
+        // The number of bytes removed before the current position, and the
+        // next range which may still overlap a segment.
+        let mut removed_before = 0;
+        let mut next = 0;
+
+        for segment in segments {
+            let mut start = segment.text.start;
+
+            while start < segment.text.end {
+                while next < removed.len() && removed[next].end <= start {
+                    removed_before += removed[next].len();
+                    next += 1;
+                }
+
+                let (end, skip_to) = match removed.get(next) {
+                    Some(range) if range.start <= start => (start, range.end),
+                    Some(range) => {
+                        let end = range.start.min(segment.text.end);
+                        (end, end)
+                    }
+                    None => (segment.text.end, segment.text.end),
+                };
+
+                if start < end {
+                    let raw = if segment.exact {
+                        let raw_start = segment.raw.start + (start - segment.text.start);
+                        raw_start..raw_start + (end - start)
+                    } else {
+                        segment.raw.clone()
+                    };
+                    self.push(
+                        start - removed_before..end - removed_before,
+                        raw,
+                        segment.exact,
+                    );
+                }
+
+                start = skip_to.min(segment.text.end).max(end);
+            }
+        }
+    }
+}
+
+/// Removes the given ranges, which must be sorted and not overlap, from a
+/// text.
+pub(crate) fn remove_ranges(text: &mut String, removed: &[Range<usize>]) {
+    let mut kept = String::with_capacity(text.len());
+    let mut position = 0;
+    for range in removed {
+        kept.push_str(&text[position..range.start]);
+        position = range.end;
+    }
+    kept.push_str(&text[position..]);
+    *text = kept;
+}
+
+/// Moves positions in a text to where they end up once the given ranges
+/// have been removed from it. Positions inside of a removed range move to
+/// where the range was.
+pub(crate) fn shift_positions<'p, I>(removed: &[Range<usize>], positions: I)
+where
+    I: IntoIterator<Item = &'p mut usize>,
+{
+    // The number of bytes removed before the end of each range
+    let totals = removed
+        .iter()
+        .scan(0, |total, range| {
+            *total += range.len();
+            Some(*total)
+        })
+        .collect::<Vec<_>>();
+
+    for position in positions {
+        let before = removed.partition_point(|r| r.end <= *position);
+        let mut shift = before.checked_sub(1).map_or(0, |i| totals[i]);
+
+        if let Some(range) = removed.get(before).filter(|r| r.start < *position) {
+            shift += *position - range.start;
+        }
+
+        *position -= shift;
+    }
+}
@@ -0,0 +1,124 @@
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread;
+use std::time::Duration;
+
+type Job = Box<dyn FnOnce() + Send>;
+
+/// A pool of threads for jobs which may take longer than the caller is
+/// willing to wait, like parsing pathological wiki text, which cannot be
+/// interrupted.
+///
+/// Workers are reused once their job is done. A worker whose caller stopped
+/// waiting is abandoned: it finishes its job in the background and then
+/// exits. Once as many workers are abandoned as rayon has threads, new jobs
+/// are handed back to the caller instead, so that the number of threads stays
+/// bounded.
+pub(crate) struct WorkerPool {
+    state: Arc<Mutex<PoolState>>,
+}
+
+#[derive(Default)]
+struct PoolState {
+    /// Workers which are waiting for a job.
+    idle: Vec<Sender<Job>>,
+    /// The number of workers which are running a job nobody waits for.
+    abandoned: usize,
+}
+
+impl WorkerPool {
+    pub(crate) fn new() -> WorkerPool {
+        WorkerPool {
+            state: Arc::new(Mutex::new(PoolState::default())),
+        }
+    }
+
+    /// Returns the number of abandoned workers which are still running.
+    pub(crate) fn abandoned(&self) -> usize {
+        lock(&self.state).abandoned
+    }
+
+    /// Runs a job on a worker, and waits at most `timeout` for its result.
+    /// Returns `Ok(None)` if the job was not done in time, and hands the job
+    /// back without running it if too many workers have been abandoned.
+    ///
+    /// A panic in the job is passed on to the caller.
+    pub(crate) fn run<T, F>(&self, timeout: Duration, job: F) -> Result<Option<T>, F>
+    where
+        T: Send + 'static,
+        F: FnOnce() -> T + Send + 'static,
+    {
+        let worker = {
+            let mut state = lock(&self.state);
+
+            if state.abandoned >= rayon::current_num_threads() {
+                return Err(job);
+            }
+
+            state.idle.pop().unwrap_or_else(spawn_worker)
+        };
+
+        let (sender, receiver) = mpsc::channel();
+        let state = Arc::clone(&self.state);
+
+        worker
+            .send(Box::new(move || {
+                let result = panic::catch_unwind(AssertUnwindSafe(job));
+
+                // The result is sent while holding the lock, so that a caller
+                // which stops waiting either still receives it, or counts the
+                // worker as abandoned before it gets here.
+                let mut state = lock(&state);
+                if sender.send(result).is_err() {
+                    state.abandoned -= 1;
+                }
+            }))
+            .expect("idle workers are always running");
+
+        let result = match receiver.recv_timeout(timeout) {
+            Ok(result) => result,
+            Err(RecvTimeoutError::Timeout) => {
+                let mut state = lock(&self.state);
+
+                match receiver.try_recv() {
+                    Ok(result) => {
+                        drop(state);
+                        result
+                    }
+                    Err(_) => {
+                        state.abandoned += 1;
+                        // Without a sender, the worker exits once it is done.
+                        drop(receiver);
+                        drop(worker);
+                        return Ok(None);
+                    }
+                }
+            }
+            Err(RecvTimeoutError::Disconnected) => unreachable!("workers always send a result"),
+        };
+
+        lock(&self.state).idle.push(worker);
+
+        match result {
+            Ok(value) => Ok(Some(value)),
+            Err(payload) => panic::resume_unwind(payload),
+        }
+    }
+}
+
+fn spawn_worker() -> Sender<Job> {
+    let (sender, jobs) = mpsc::channel::<Job>();
+
+    thread::spawn(move || {
+        for job in jobs {
+            job();
+        }
+    });
+
+    sender
+}
+
+fn lock(state: &Mutex<PoolState>) -> MutexGuard<'_, PoolState> {
+    state.lock().expect("worker pool is never poisoned")
+}
@@ -0,0 +1,231 @@
+use crate::Parser;
+use std::io::{self, Read};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// How far the parser has got through a dump, which is passed to the
+/// callback set with [on_progress](struct.Parser.html#method.on_progress).
+///
+/// The pages of a split dump are counted from the start of the first part,
+/// as if the parts were a single dump file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Progress {
+    /// The number of bytes of XML which have been read, after decompressing.
+    pub bytes_read: u64,
+    /// The number of bytes which have been read from the dump file on disk,
+    /// which is less than `bytes_read` for compressed dumps.
+    pub file_bytes_read: u64,
+    /// The number of bytes of the dump file which were skipped, because the
+    /// parser resumed from a checkpoint. These are not counted in
+    /// `file_bytes_read`.
+    pub file_bytes_skipped: u64,
+    /// The size of the dump file on disk, if it is known.
+    pub file_size: Option<u64>,
+    /// The number of pages which have been read, whether or not they were
+    /// kept.
+    pub pages_seen: u64,
+    /// The number of pages which have been passed on by the parser.
+    pub pages_kept: u64,
+    /// The number of pages which have been skipped, because they are
+    /// excluded by the parser, were skipped by a visitor, or could not be
+    /// read by a lenient parser.
+    pub pages_skipped: u64,
+    /// The time since the parser started reading the dump.
+    pub elapsed: Duration,
+}
+
+impl Progress {
+    /// Returns the number of bytes of XML read per second.
+    pub fn bytes_per_second(&self) -> f64 {
+        per_second(self.bytes_read, self.elapsed)
+    }
+
+    /// Returns the number of pages read per second.
+    pub fn pages_per_second(&self) -> f64 {
+        per_second(self.pages_seen, self.elapsed)
+    }
+
+    /// Returns how much of the dump file has been read or skipped, from 0 to
+    /// 1, if the size of the file is known.
+    pub fn fraction(&self) -> Option<f64> {
+        let done = self.file_bytes_skipped + self.file_bytes_read;
+
+        self.file_size
+            .filter(|&size| size > 0)
+            .map(|size| (done as f64 / size as f64).min(1.0))
+    }
+
+    /// Returns an estimate of the time left until the whole dump has been
+    /// read, if the size of the file is known.
+    pub fn remaining(&self) -> Option<Duration> {
+        let done = self.file_bytes_skipped + self.file_bytes_read;
+        let left = self.file_size?.saturating_sub(done);
+        let rate = per_second(self.file_bytes_read, self.elapsed);
+
+        if left == 0 {
+            Some(Duration::ZERO)
+        } else if rate > 0.0 {
+            Some(Duration::from_secs_f64(left as f64 / rate))
+        } else {
+            None
+        }
+    }
+}
+
+fn per_second(count: u64, elapsed: Duration) -> f64 {
+    match elapsed.as_secs_f64() {
+        seconds if seconds > 0.0 => count as f64 / seconds,
+        _ => 0.0,
+    }
+}
+
+/// A callback which receives the progress of the parser.
+pub(crate) type ProgressCallback = Box<dyn Fn(&Progress) + Send + Sync>;
+
+/// Counts the bytes which are read from a dump file, before they are
+/// decompressed.
+pub(crate) struct CountingReader<R> {
+    inner: R,
+    count: Arc<AtomicU64>,
+}
+
+impl<R> CountingReader<R> {
+    pub(crate) fn new(inner: R) -> CountingReader<R> {
+        CountingReader {
+            inner,
+            count: Arc::default(),
+        }
+    }
+
+    /// Returns a handle to the number of bytes read so far.
+    pub(crate) fn count(&self) -> Arc<AtomicU64> {
+        Arc::clone(&self.count)
+    }
+}
+
+impl<R: Read> Read for CountingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.count.fetch_add(read as u64, Ordering::Relaxed);
+        Ok(read)
+    }
+}
+
+/// Keeps track of the progress of a dump reader, and passes it to the
+/// callback of the parser every so often.
+pub(crate) struct ProgressTracker {
+    /// The page counts and file size. The byte counts and elapsed time are
+    /// filled in when the progress is reported.
+    progress: Progress,
+    /// The bytes read by the readers of earlier parts of a split dump.
+    earlier_bytes_read: u64,
+    earlier_file_bytes_read: u64,
+    /// The bytes read from the dump file, if the dump is read from a file.
+    file_bytes_read: Option<Arc<AtomicU64>>,
+    started: Instant,
+    last_reported: Option<Instant>,
+}
+
+impl ProgressTracker {
+    pub(crate) fn new() -> ProgressTracker {
+        ProgressTracker {
+            progress: Progress::default(),
+            earlier_bytes_read: 0,
+            earlier_file_bytes_read: 0,
+            file_bytes_read: None,
+            started: Instant::now(),
+            last_reported: None,
+        }
+    }
+
+    /// Counts the bytes read from the dump file with `file_bytes_read`,
+    /// rather than assuming they are the same as the bytes of XML.
+    pub(crate) fn set_file(&mut self, file_bytes_read: Arc<AtomicU64>, file_size: u64) {
+        self.file_bytes_read = Some(file_bytes_read);
+        self.progress.file_size = Some(file_size);
+    }
+
+    pub(crate) fn set_file_size(&mut self, file_size: Option<u64>) {
+        self.progress.file_size = file_size;
+    }
+
+    /// Counts bytes of the dump file which were skipped rather than read.
+    pub(crate) fn skip_file_bytes(&mut self, bytes: u64) {
+        self.progress.file_bytes_skipped += bytes;
+    }
+
+    /// Carries on counting from where the tracker of the previous part of a
+    /// split dump left off.
+    pub(crate) fn continue_from(&mut self, previous: &ProgressTracker, bytes_read: u64) {
+        let progress = previous.current(bytes_read);
+
+        self.earlier_bytes_read = progress.bytes_read;
+        self.earlier_file_bytes_read = progress.file_bytes_read;
+        self.progress = Progress {
+            bytes_read: 0,
+            file_bytes_read: 0,
+            elapsed: Duration::ZERO,
+            ..progress
+        };
+        self.started = previous.started;
+        self.last_reported = previous.last_reported;
+    }
+
+    pub(crate) fn pages_kept(&self) -> u64 {
+        self.progress.pages_kept
+    }
+
+    /// Returns the number of bytes of XML read by every reader so far, given
+    /// the number which the current reader has read.
+    pub(crate) fn total_bytes_read(&self, bytes_read: u64) -> u64 {
+        self.earlier_bytes_read + bytes_read
+    }
+
+    pub(crate) fn page_kept(&mut self) {
+        self.progress.pages_seen += 1;
+        self.progress.pages_kept += 1;
+    }
+
+    pub(crate) fn page_skipped(&mut self) {
+        self.progress.pages_seen += 1;
+        self.progress.pages_skipped += 1;
+    }
+
+    /// Passes the progress to the callback of the parser, if there is one,
+    /// once its interval has passed since the last call. If `force` is true,
+    /// the callback is called regardless of the interval.
+    pub(crate) fn report(&mut self, parser: &Parser, bytes_read: u64, force: bool) {
+        let Some(callback) = parser.progress_callback.as_ref() else {
+            return;
+        };
+
+        let now = Instant::now();
+        let due = match self.last_reported {
+            Some(last) => now.duration_since(last) >= parser.progress_interval,
+            None => true,
+        };
+
+        if force || due {
+            self.last_reported = Some(now);
+            callback(&self.current(bytes_read));
+        }
+    }
+
+    /// Returns the progress, given the number of bytes of XML which the
+    /// current reader has read.
+    fn current(&self, bytes_read: u64) -> Progress {
+        let file_bytes_read = self
+            .file_bytes_read
+            .as_ref()
+            .map_or(bytes_read, |count| count.load(Ordering::Relaxed));
+
+        Progress {
+            bytes_read: self.total_bytes_read(bytes_read),
+            file_bytes_read: self.earlier_file_bytes_read + file_bytes_read,
+            elapsed: self.started.elapsed(),
+            ..self.progress.clone()
+        }
+    }
+}
@@ -0,0 +1,910 @@
+use crate::checkpoint::{StreamStart, StreamStarts};
+use crate::progress::ProgressTracker;
+use crate::{
+    Comment, Contributor, Error, Exception, FailedPage, Namespace, Page, PageRevision, PageVisitor,
+    ParseReport, Parser, RevisionSelection, RevisionSlot, Site, TextContent, Visit,
+};
+use quick_xml::escape::unescape;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::name::QName;
+use quick_xml::reader::Reader;
+use std::io::BufRead;
+
+/// Reads a single dump file one page at a time. This holds on to the XML
+/// reader and its buffers between pages, so that any number of pages can be
+/// read without keeping the whole dump in memory.
+pub(crate) struct DumpReader<R> {
+    reader: Reader<R>,
+    buf: Vec<u8>,
+    text_buf: Vec<u8>,
+    /// The title of the current page, before it has been unescaped.
+    raw_title: Vec<u8>,
+    /// The number of revisions read so far of a page which was left part way
+    /// through, after one of its revisions was read on its own.
+    open_page: Option<usize>,
+    pub(crate) progress: ProgressTracker,
+    /// The position in the uncompressed dump where reading started, which
+    /// is only past the start for a reader which resumed from a checkpoint.
+    start_position: u64,
+    /// The number of bytes which were put in front of the dump when
+    /// resuming, which are not part of the dump itself.
+    prefix_len: u64,
+    /// The starts of the bzip2 streams of a compressed dump.
+    pub(crate) streams: Option<StreamStarts>,
+}
+
+impl<R> DumpReader<R>
+where
+    R: BufRead,
+{
+    /// Creates a reader for a dump. Unless `strict` is true, the names of end
+    /// tags are not checked, which saves time by assuming well formed XML is
+    /// passed in.
+    pub(crate) fn new(mut reader: Reader<R>, strict: bool) -> DumpReader<R> {
+        let config = reader.config_mut();
+        config.check_end_names = strict;
+        config.trim_markup_names_in_closing_tags = false;
+
+        DumpReader {
+            reader,
+            buf: Vec::new(),
+            text_buf: Vec::new(),
+            raw_title: Vec::new(),
+            open_page: None,
+            progress: ProgressTracker::new(),
+            start_position: 0,
+            prefix_len: 0,
+            streams: None,
+        }
+    }
+
+    /// Carries on reading from `position` in the dump, after the XML reader
+    /// has been given a prefix of `prefix_len` bytes followed by the dump
+    /// from that position on. This skips ahead to the first page.
+    pub(crate) fn resume(&mut self, position: u64, prefix_len: u64) -> Result<(), Exception> {
+        self.start_position = position;
+        self.prefix_len = prefix_len;
+
+        loop {
+            self.buf.clear();
+
+            match self.reader.read_event_into(&mut self.buf) {
+                Ok(Event::Start(ref e)) if e.name().as_ref() == b"page" => return Ok(()),
+                Ok(Event::Eof) => return Ok(()),
+                Err(e) => return Err(xml_error(&self.reader, e).into()),
+                _ => {}
+            }
+        }
+    }
+
+    /// Returns the position in the uncompressed dump which the XML reader
+    /// has reached.
+    pub(crate) fn position(&self) -> u64 {
+        self.position_at(self.reader.buffer_position())
+    }
+
+    /// Turns a position of the XML reader into a position in the dump.
+    fn position_at(&self, buffer_position: u64) -> u64 {
+        self.start_position + buffer_position.saturating_sub(self.prefix_len)
+    }
+
+    /// Returns the number of bytes of the dump which have been read.
+    fn bytes_read(&self) -> u64 {
+        self.position() - self.start_position
+    }
+
+    /// Returns where the bzip2 stream which holds the current position
+    /// starts, or the current position itself for uncompressed dumps.
+    pub(crate) fn stream_start(&self) -> StreamStart {
+        let position = self.position();
+
+        match self.streams.as_ref() {
+            Some(streams) => streams
+                .lock()
+                .expect("stream starts are never poisoned")
+                .iter()
+                .rev()
+                .find(|s| s.position <= position)
+                .copied()
+                .unwrap_or(StreamStart {
+                    offset: 0,
+                    position: 0,
+                }),
+            None => StreamStart {
+                offset: position,
+                position,
+            },
+        }
+    }
+
+    /// Forgets the starts of the streams which come before the stream that
+    /// holds the current position, since they are no longer needed.
+    fn forget_old_streams(&mut self) {
+        let position = self.position();
+
+        if let Some(streams) = self.streams.as_ref() {
+            let mut streams = streams.lock().expect("stream starts are never poisoned");
+
+            while streams.get(1).is_some_and(|s| s.position <= position) {
+                streams.pop_front();
+            }
+        }
+    }
+
+    /// Returns true once the parser has read as many pages or bytes as it is
+    /// allowed to.
+    pub(crate) fn limit_reached(&self, parser: &Parser) -> bool {
+        let bytes_read = self.progress.total_bytes_read(self.bytes_read());
+
+        parser
+            .max_pages
+            .is_some_and(|max| self.progress.pages_kept() >= max)
+            || parser.max_bytes.is_some_and(|max| bytes_read >= max)
+    }
+
+    /// Carries on counting progress from where the reader of the previous
+    /// part of a split dump left off.
+    pub(crate) fn continue_from(&mut self, previous: &DumpReader<R>) {
+        self.progress
+            .continue_from(&previous.progress, previous.bytes_read());
+    }
+
+    /// Passes the progress of the reader to the callback of the parser.
+    fn report_progress(&mut self, parser: &Parser, force: bool) {
+        let bytes_read = self.bytes_read();
+        self.progress.report(parser, bytes_read, force);
+    }
+
+    /// Reads the `<siteinfo>` header of the dump into `site`. This stops at
+    /// the end of the header, or at the first page for dumps which have no
+    /// header at all.
+    pub(crate) fn read_site_info(&mut self, site: &mut Site) -> Result<(), Exception> {
+        loop {
+            self.buf.clear();
+            self.text_buf.clear();
+
+            match self.reader.read_event_into(&mut self.buf) {
+                Ok(Event::Start(ref e)) => match e.name().as_ref() {
+                    b"sitename" => {
+                        site.name = get_text_from_event(&mut self.reader, &mut self.text_buf)?
+                    }
+                    b"dbname" => {
+                        site.dbname = get_text_from_event(&mut self.reader, &mut self.text_buf)?
+                    }
+                    b"base" => {
+                        site.url = get_text_from_event(&mut self.reader, &mut self.text_buf)?
+                    }
+                    b"generator" => {
+                        site.generator = get_text_from_event(&mut self.reader, &mut self.text_buf)?
+                    }
+                    b"case" => {
+                        site.case = get_text_from_event(&mut self.reader, &mut self.text_buf)?
+                    }
+                    b"namespace" => {
+                        let mut namespace = read_namespace_attributes(e);
+                        namespace.name = get_text_from_event(&mut self.reader, &mut self.text_buf)?;
+                        site.namespaces.push(namespace);
+                    }
+                    b"page" => break,
+                    _ => {}
+                },
+                // The main namespace has no name, so it is an empty element.
+                Ok(Event::Empty(ref e)) if e.name().as_ref() == b"namespace" => {
+                    site.namespaces.push(read_namespace_attributes(e))
+                }
+                Ok(Event::End(ref e)) if e.name().as_ref() == b"siteinfo" => break,
+                Ok(Event::Eof) => break,
+                Err(e) => return Err(xml_error(&self.reader, e).into()),
+                _ => (),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Reads the next page which is not excluded by `parser` into `page`,
+    /// replacing its previous contents. The wiki text is left unprocessed,
+    /// unless there is a visitor, in which case each revision is processed
+    /// before it is passed to the visitor.
+    ///
+    /// Returns false once the end of the dump has been reached, once the
+    /// visitor has stopped, or once the limits of the parser have been
+    /// reached.
+    pub(crate) fn read_page(
+        &mut self,
+        parser: &Parser,
+        page: &mut Page,
+        visitor: Option<&mut (dyn PageVisitor + '_)>,
+    ) -> Result<bool, Exception> {
+        parser.check_cancelled()?;
+
+        if self.limit_reached(parser) {
+            self.report_progress(parser, true);
+            return Ok(false);
+        }
+
+        self.read_page_events(parser, page, visitor)
+            .map_err(|e| self.locate_error(e))
+    }
+
+    /// Reads the next revision which is selected by `parser`, one at a time
+    /// rather than a page at a time, and returns it. The page it belongs to
+    /// is kept in `page`, without its revisions, until the page has been read
+    /// to its end. The wiki text is left unprocessed.
+    ///
+    /// Returns `None` once the end of the dump has been reached, or once the
+    /// limits of the parser have been reached.
+    pub(crate) fn read_revision(
+        &mut self,
+        parser: &Parser,
+        page: &mut Page,
+    ) -> Result<Option<PageRevision>, Exception> {
+        parser.check_cancelled()?;
+
+        loop {
+            if self.open_page.is_none() {
+                if self.limit_reached(parser) {
+                    self.report_progress(parser, true);
+                    return Ok(None);
+                }
+
+                page.reset();
+            }
+
+            let end = self
+                .read_one_page(parser, page, None, true)
+                .map_err(|e| self.locate_error(e))?;
+
+            match end {
+                PageEnd::Revision => return Ok(page.revisions.pop()),
+                PageEnd::Kept => {
+                    self.forget_old_streams();
+                    self.progress.page_kept();
+                    self.report_progress(parser, false);
+
+                    // The last revision is only known once the page ends.
+                    if let Some(revision) = page.revisions.pop() {
+                        return Ok(Some(revision));
+                    }
+                }
+                PageEnd::Skipped => {
+                    self.progress.page_skipped();
+                    self.report_progress(parser, false);
+                }
+                PageEnd::Stopped => return Ok(None),
+            }
+        }
+    }
+
+    /// Errors are found at positions of the XML reader, which only match
+    /// positions in the dump if the reader did not resume.
+    fn locate_error(&self, mut e: Exception) -> Exception {
+        if let Some(Error::Xml { position, .. }) = e.downcast_mut::<Error>() {
+            *position = self.position_at(*position);
+        }
+
+        e
+    }
+
+    fn read_page_events(
+        &mut self,
+        parser: &Parser,
+        page: &mut Page,
+        mut visitor: Option<&mut (dyn PageVisitor + '_)>,
+    ) -> Result<bool, Exception> {
+        loop {
+            page.reset();
+
+            match self.read_one_page(parser, page, visitor.as_deref_mut(), false)? {
+                PageEnd::Kept => {
+                    self.forget_old_streams();
+                    self.progress.page_kept();
+                    self.report_progress(parser, false);
+                    return Ok(true);
+                }
+                PageEnd::Skipped => {
+                    self.progress.page_skipped();
+                    self.report_progress(parser, false);
+                }
+                PageEnd::Revision => unreachable!("revisions are only read on their own"),
+                PageEnd::Stopped => return Ok(false),
+            }
+        }
+    }
+
+    /// Reads a single page into `page`, which is expected to be empty. If
+    /// `revisions` is true, this stops after each selected revision instead,
+    /// which is left in `page`, and carries on with the same page when it is
+    /// called again.
+    fn read_one_page(
+        &mut self,
+        parser: &Parser,
+        page: &mut Page,
+        mut visitor: Option<&mut (dyn PageVisitor + '_)>,
+        revisions: bool,
+    ) -> Result<PageEnd, Exception> {
+        let mut current_page_revision = PageRevision::new();
+        let mut current_slot = RevisionSlot::new();
+        // True while reading a `<content>` element, which holds a slot other
+        // than the main slot in export schema 0.11.
+        let mut in_slot = false;
+        let mut in_revision = false;
+        let mut in_contributor = false;
+        // Set once the page has been rejected, after which the rest of it is
+        // skipped without being read.
+        let mut skipping_current_page = false;
+        // Set once the current revision has not been selected, after which
+        // the rest of it is skipped without being read.
+        let mut skipping_revision = false;
+        // A page which was left part way through carries on where it was left.
+        let open_page = self.open_page.take();
+        // The number of revisions of the page before the current one.
+        let mut revision_index = open_page.unwrap_or(0);
+        // True once the visitor has been told about the current page.
+        let mut page_started = false;
+        // True once any part of a page has been read, so that a dump which
+        // ends in the middle of a page can be told apart from one which ends
+        // after its last page.
+        let mut in_page = open_page.is_some();
+
+        if open_page.is_none() {
+            self.raw_title.clear();
+        }
+
+        loop {
+            // if we don't keep a borrow elsewhere, we can clear the buffer to keep memory usage low
+            self.buf.clear();
+            self.text_buf.clear();
+
+            match self.reader.read_event_into(&mut self.buf) {
+                Ok(Event::Start(ref e)) => {
+                    in_page = true;
+
+                    match e.name().as_ref() {
+                        b"text" if in_slot => {
+                            current_slot.text =
+                                get_text_from_event(&mut self.reader, &mut self.text_buf)?
+                        }
+                        b"text"
+                            if !parser
+                                .revision_selection
+                                .selects(revision_index, &current_page_revision) =>
+                        {
+                            skipping_revision = true;
+                        }
+                        b"text" => {
+                            read_text_attributes(e, &mut current_page_revision);
+
+                            if current_page_revision.text_content != TextContent::Deleted {
+                                current_page_revision.text_content = TextContent::Included;
+                            }
+
+                            current_page_revision.raw =
+                                get_text_from_event(&mut self.reader, &mut self.text_buf)?
+                        }
+                        b"model" if in_slot => {
+                            current_slot.model =
+                                get_text_from_event(&mut self.reader, &mut self.text_buf)?
+                        }
+                        b"model" => {
+                            current_page_revision.model =
+                                get_text_from_event(&mut self.reader, &mut self.text_buf)?
+                        }
+                        b"format" if in_slot => {
+                            current_slot.format =
+                                get_text_from_event(&mut self.reader, &mut self.text_buf)?
+                        }
+                        b"format" => {
+                            current_page_revision.format =
+                                get_text_from_event(&mut self.reader, &mut self.text_buf)?
+                        }
+                        b"role" if in_slot => {
+                            current_slot.role =
+                                get_text_from_event(&mut self.reader, &mut self.text_buf)?
+                        }
+                        b"origin" if in_slot => {
+                            current_slot.origin =
+                                get_number_from_event(&mut self.reader, &mut self.text_buf)?
+                        }
+                        b"origin" => {
+                            current_page_revision.origin =
+                                get_number_from_event(&mut self.reader, &mut self.text_buf)?
+                        }
+                        b"content" => in_slot = true,
+                        b"revision" => {
+                            in_revision = true;
+
+                            if let Some(visitor) = visitor.as_mut() {
+                                if !page_started {
+                                    page_started = true;
+                                    unescape_title(&self.reader, &self.raw_title, page)?;
+
+                                    match visitor.on_page_start(page) {
+                                        Visit::Continue => {}
+                                        Visit::Skip => skipping_current_page = true,
+                                        Visit::Stop => return Ok(PageEnd::Stopped),
+                                    }
+                                }
+                            }
+
+                            if !parser.revision_selection.may_select(revision_index) {
+                                skipping_revision = true;
+                            }
+                        }
+                        b"contributor" => in_contributor = true,
+                        b"username" if in_contributor => {
+                            current_page_revision.contributor = Contributor::User {
+                                id: 0,
+                                username: get_text_from_event(
+                                    &mut self.reader,
+                                    &mut self.text_buf,
+                                )?,
+                            }
+                        }
+                        b"ip" if in_contributor => {
+                            current_page_revision.contributor = Contributor::Anonymous {
+                                ip: get_text_from_event(&mut self.reader, &mut self.text_buf)?,
+                            }
+                        }
+                        b"id" if in_contributor => {
+                            if let Contributor::User { ref mut id, .. } =
+                                current_page_revision.contributor
+                            {
+                                *id = get_number_from_event(&mut self.reader, &mut self.text_buf)?
+                                    .unwrap_or_default();
+                            }
+                        }
+                        b"comment" => {
+                            current_page_revision.comment = Comment::Text(get_text_from_event(
+                                &mut self.reader,
+                                &mut self.text_buf,
+                            )?)
+                        }
+                        b"id" if in_revision => {
+                            current_page_revision.id =
+                                get_number_from_event(&mut self.reader, &mut self.text_buf)?
+                                    .unwrap_or_default()
+                        }
+                        b"id" => {
+                            page.id = get_number_from_event(&mut self.reader, &mut self.text_buf)?
+                                .unwrap_or_default()
+                        }
+                        b"parentid" => {
+                            current_page_revision.parent_id =
+                                get_number_from_event(&mut self.reader, &mut self.text_buf)?
+                        }
+                        b"timestamp" => {
+                            current_page_revision.timestamp =
+                                get_text_from_event(&mut self.reader, &mut self.text_buf)?
+                        }
+                        b"sha1" if in_revision => {
+                            current_page_revision.sha1 =
+                                get_text_from_event(&mut self.reader, &mut self.text_buf)?
+                        }
+                        b"title" => {
+                            // The title is only unescaped once it is needed,
+                            // since most pages of a dump may be excluded.
+                            get_raw_text_from_event(
+                                &mut self.reader,
+                                &mut self.text_buf,
+                                &mut self.raw_title,
+                            )?;
+
+                            if let Some(filter) = parser.title_filter.as_ref() {
+                                unescape_title(&self.reader, &self.raw_title, page)?;
+
+                                if !filter.matches(&page.title) {
+                                    // Skip this page
+                                    skipping_current_page = true;
+                                }
+                            }
+                        }
+                        b"ns" => {
+                            page.namespace =
+                                get_number_from_event(&mut self.reader, &mut self.text_buf)?
+                                    .unwrap_or_default();
+
+                            if parser.exclude_pages && page.namespace != 0 {
+                                // Skip this page
+                                skipping_current_page = true;
+                            }
+                        }
+                        _ => {}
+                    };
+                }
+                Ok(Event::Empty(ref e)) => {
+                    match e.name().as_ref() {
+                        // Stub dumps refer to the text of a revision by its
+                        // ID with an empty element, rather than including it.
+                        // Revisions which blanked the page have an empty
+                        // element without an ID instead.
+                        b"text" if !in_slot => {
+                            read_text_attributes(e, &mut current_page_revision);
+
+                            if current_page_revision.text_content != TextContent::Deleted
+                                && (current_page_revision.text_id.is_none()
+                                    || has_attribute(e, b"xml:space"))
+                            {
+                                current_page_revision.text_content = TextContent::Included;
+                            }
+                        }
+                        // Hidden comments and contributors are also marked
+                        // with an empty element.
+                        b"comment" if is_deleted(e) => {
+                            current_page_revision.comment = Comment::Deleted
+                        }
+                        b"contributor" if is_deleted(e) => {
+                            current_page_revision.contributor = Contributor::Deleted
+                        }
+                        _ => {}
+                    };
+                }
+                Ok(Event::End(ref e)) => {
+                    match e.name().as_ref() {
+                        b"page" => {
+                            unescape_title(&self.reader, &self.raw_title, page)?;
+
+                            // Pages without revisions still need to be
+                            // started before they end.
+                            if let Some(visitor) = visitor.as_mut().filter(|_| !page_started) {
+                                match visitor.on_page_start(page) {
+                                    Visit::Continue => {}
+                                    Visit::Skip => return Ok(PageEnd::Skipped),
+                                    Visit::Stop => return Ok(PageEnd::Stopped),
+                                }
+                            }
+
+                            // The last revision is only known now.
+                            let latest =
+                                matches!(parser.revision_selection, RevisionSelection::Latest);
+
+                            if let Some(visitor) = visitor.as_mut().filter(|_| latest) {
+                                if let Some(mut revision) = page.revisions.pop() {
+                                    parser.process_revision(&mut revision);
+
+                                    match visitor.on_revision(page, &revision) {
+                                        Visit::Continue => page.revisions.push(revision),
+                                        Visit::Skip => return Ok(PageEnd::Skipped),
+                                        Visit::Stop => return Ok(PageEnd::Stopped),
+                                    }
+                                }
+                            }
+
+                            return Ok(PageEnd::Kept);
+                        }
+                        b"revision" => {
+                            // Pages with many revisions can take a while.
+                            parser.check_cancelled()?;
+
+                            let selection = &parser.revision_selection;
+                            let selected =
+                                selection.selects(revision_index, &current_page_revision);
+                            revision_index += 1;
+
+                            if selected && matches!(selection, RevisionSelection::Latest) {
+                                // Only the last revision is kept, and it is
+                                // passed to the visitor once the page ends.
+                                page.revisions.clear();
+                                page.revisions.push(std::mem::replace(
+                                    &mut current_page_revision,
+                                    PageRevision::new(),
+                                ));
+                            } else if selected && revisions {
+                                unescape_title(&self.reader, &self.raw_title, page)?;
+                                page.revisions.push(current_page_revision);
+                                self.open_page = Some(revision_index);
+                                return Ok(PageEnd::Revision);
+                            } else if selected {
+                                if let Some(visitor) = visitor.as_mut() {
+                                    parser.process_revision(&mut current_page_revision);
+
+                                    match visitor.on_revision(page, &current_page_revision) {
+                                        Visit::Continue => {}
+                                        Visit::Skip => skipping_current_page = true,
+                                        Visit::Stop => return Ok(PageEnd::Stopped),
+                                    }
+                                }
+
+                                page.revisions.push(current_page_revision.clone());
+                            }
+
+                            current_page_revision.reset();
+                            in_revision = false;
+                        }
+                        b"contributor" => in_contributor = false,
+                        b"content" => {
+                            current_page_revision.slots.push(current_slot);
+                            current_slot = RevisionSlot::new();
+                            in_slot = false;
+                        }
+                        _ => {}
+                    };
+                }
+                Ok(Event::Eof) if in_page => {
+                    return Err(
+                        xml_error(&self.reader, "unexpected end of file inside a page").into(),
+                    )
+                }
+                Ok(Event::Eof) => {
+                    self.report_progress(parser, true);
+                    return Ok(PageEnd::Stopped);
+                }
+                Err(e) => return Err(xml_error(&self.reader, e).into()),
+                _ => (), // There are several other `Event`s we do not consider here
+            }
+
+            if skipping_current_page {
+                // Jump straight to the end of the page, without decoding
+                // anything else in it.
+                self.buf.clear();
+                self.reader
+                    .read_to_end_into(QName(b"page"), &mut self.buf)
+                    .map_err(|e| xml_error(&self.reader, e))?;
+
+                return Ok(PageEnd::Skipped);
+            }
+
+            if skipping_revision {
+                // Likewise, jump straight to the end of the revision.
+                self.buf.clear();
+                self.reader
+                    .read_to_end_into(QName(b"revision"), &mut self.buf)
+                    .map_err(|e| xml_error(&self.reader, e))?;
+
+                skipping_revision = false;
+                revision_index += 1;
+                current_page_revision.reset();
+                current_slot = RevisionSlot::new();
+                in_revision = false;
+                in_contributor = false;
+                in_slot = false;
+            }
+        }
+    }
+
+    /// Reads the next page like [read_page](#method.read_page). If the
+    /// parser is lenient, pages which cannot be read are recorded in `report`
+    /// and passed to the visitor, and reading carries on with the next page.
+    pub(crate) fn next_page(
+        &mut self,
+        parser: &Parser,
+        page: &mut Page,
+        mut visitor: Option<&mut (dyn PageVisitor + '_)>,
+        report: &mut ParseReport,
+    ) -> Result<bool, Exception> {
+        loop {
+            let error = match self.read_page(parser, page, visitor.as_deref_mut()) {
+                Err(e) if parser.lenient && !is_cancelled(&e) => e,
+                result => return result,
+            };
+
+            if !self.skip_failed_page(page, error, visitor.as_deref_mut(), report) {
+                return Ok(false);
+            }
+        }
+    }
+
+    /// Reads the next revision like [read_revision](#method.read_revision).
+    /// If the parser is lenient, pages which cannot be read are recorded in
+    /// `report` like they are by [next_page](#method.next_page), even if some
+    /// of their revisions were already read.
+    pub(crate) fn next_revision(
+        &mut self,
+        parser: &Parser,
+        page: &mut Page,
+        report: &mut ParseReport,
+    ) -> Result<Option<PageRevision>, Exception> {
+        loop {
+            let error = match self.read_revision(parser, page) {
+                Err(e) if parser.lenient && !is_cancelled(&e) => e,
+                result => return result,
+            };
+
+            self.open_page = None;
+
+            if !self.skip_failed_page(page, error, None, report) {
+                return Ok(None);
+            }
+        }
+    }
+
+    /// Records a page which could not be read, and skips ahead to the next
+    /// page. Returns false if reading should not carry on.
+    fn skip_failed_page(
+        &mut self,
+        page: &mut Page,
+        error: Exception,
+        visitor: Option<&mut (dyn PageVisitor + '_)>,
+        report: &mut ParseReport,
+    ) -> bool {
+        // The title may not have been needed before the page failed.
+        let _ = unescape_title(&self.reader, &self.raw_title, page);
+        let failed = FailedPage {
+            title: Some(page.title.clone()).filter(|t| !t.is_empty()),
+            position: self.position(),
+            error: error.to_string(),
+        };
+        let visit = visitor.map_or(Visit::Continue, |v| v.on_failed_page(&failed));
+        report.failed_pages.push(failed);
+        self.progress.page_skipped();
+
+        visit != Visit::Stop && self.skip_to_next_page()
+    }
+
+    /// Skips ahead to the start of the next page, after a page which could
+    /// not be read. Returns false if there are no more pages, or if the rest
+    /// of the dump cannot be read either.
+    fn skip_to_next_page(&mut self) -> bool {
+        loop {
+            let position = self.reader.buffer_position();
+            self.buf.clear();
+
+            match self.reader.read_event_into(&mut self.buf) {
+                Ok(Event::Start(ref e)) if e.name().as_ref() == b"page" => return true,
+                Ok(Event::Eof) => return false,
+                // Give up if the reader is stuck on the same error.
+                Err(_) if self.reader.buffer_position() == position => return false,
+                _ => {}
+            }
+        }
+    }
+}
+
+/// How reading a single page ended.
+enum PageEnd {
+    /// The page was read, and should be passed on.
+    Kept,
+    /// The page was rejected, and the reader has moved past it.
+    Skipped,
+    /// A revision was read on its own, and the rest of the page is still to
+    /// be read.
+    Revision,
+    /// The end of the dump was reached, or the visitor stopped.
+    Stopped,
+}
+
+/// Given a reader which has just read a start event, this function will read
+/// the text event which follows it and return the text as a string. This is
+/// useful for quickly reading the text of a simple element.
+fn get_text_from_event<R>(reader: &mut Reader<R>, text_buf: &mut Vec<u8>) -> Result<String, Error>
+where
+    R: BufRead,
+{
+    let event = reader
+        .read_event_into(text_buf)
+        .map_err(|e| xml_error(reader, e))?;
+
+    match event {
+        Event::Text(e) => Ok(e.unescape().map_err(|e| xml_error(reader, e))?.into_owned()),
+        // There was nothing in the text, so just return an empty string
+        Event::End(_) => Ok("".to_string()),
+        _ => Err(xml_error(
+            reader,
+            format!("expected the text of an element, got {:?}", event),
+        )),
+    }
+}
+
+/// Reads the text of a simple element like [get_text_from_event], but copies
+/// it into `raw` as-is rather than unescaping it.
+fn get_raw_text_from_event<R>(
+    reader: &mut Reader<R>,
+    text_buf: &mut Vec<u8>,
+    raw: &mut Vec<u8>,
+) -> Result<(), Error>
+where
+    R: BufRead,
+{
+    let event = reader
+        .read_event_into(text_buf)
+        .map_err(|e| xml_error(reader, e))?;
+
+    match event {
+        Event::Text(e) => raw.extend_from_slice(&e),
+        Event::End(_) => {}
+        _ => {
+            return Err(xml_error(
+                reader,
+                format!("expected the text of an element, got {:?}", event),
+            ))
+        }
+    }
+
+    Ok(())
+}
+
+/// Unescapes the raw title of a page into its title, unless this has been
+/// done already.
+fn unescape_title<R>(reader: &Reader<R>, raw: &[u8], page: &mut Page) -> Result<(), Error> {
+    if !page.title.is_empty() || raw.is_empty() {
+        return Ok(());
+    }
+
+    let raw = std::str::from_utf8(raw).map_err(|e| xml_error(reader, e))?;
+    page.title = unescape(raw)
+        .map_err(|e| xml_error(reader, e))?
+        .into_owned();
+    Ok(())
+}
+
+/// Reads the text of a simple element like [get_text_from_event], and parses
+/// it as a number. Returns `None` if the element is empty or not a number.
+fn get_number_from_event<R, N>(
+    reader: &mut Reader<R>,
+    text_buf: &mut Vec<u8>,
+) -> Result<Option<N>, Error>
+where
+    R: BufRead,
+    N: std::str::FromStr,
+{
+    Ok(get_text_from_event(reader, text_buf)?.trim().parse().ok())
+}
+
+/// Returns true if an error is due to the parse being cancelled, rather than
+/// due to a problem with the dump.
+fn is_cancelled(error: &Exception) -> bool {
+    matches!(error.downcast_ref(), Some(Error::Cancelled))
+}
+
+/// Returns an error for a problem found at the current position of `reader`.
+fn xml_error<R, M>(reader: &Reader<R>, message: M) -> Error
+where
+    M: ToString,
+{
+    Error::Xml {
+        position: reader.buffer_position(),
+        message: message.to_string(),
+    }
+}
+
+/// Reads the attributes of a `<text>` element, which describe the text of a
+/// revision even when the text itself is not included in the dump.
+fn read_text_attributes(e: &BytesStart, revision: &mut PageRevision) {
+    for attribute in e.attributes().flatten() {
+        let value = std::str::from_utf8(&attribute.value).unwrap_or_default();
+
+        match attribute.key.as_ref() {
+            b"bytes" => revision.text_bytes = value.parse().ok(),
+            b"id" => revision.text_id = value.parse().ok(),
+            _ => {}
+        }
+    }
+
+    if is_deleted(e) {
+        revision.text_content = TextContent::Deleted;
+    }
+}
+
+/// Reads the attributes of a `<namespace>` element in the siteinfo.
+fn read_namespace_attributes(e: &BytesStart) -> Namespace {
+    let mut namespace = Namespace {
+        key: 0,
+        case: "".to_string(),
+        name: "".to_string(),
+    };
+
+    for attribute in e.attributes().flatten() {
+        let value = std::str::from_utf8(&attribute.value).unwrap_or_default();
+
+        match attribute.key.as_ref() {
+            b"key" => namespace.key = value.parse().unwrap_or_default(),
+            b"case" => namespace.case = value.to_string(),
+            _ => {}
+        }
+    }
+
+    namespace
+}
+
+/// Returns true if an element has been marked as deleted, which is how dumps
+/// hide content that has been deleted or suppressed by an administrator.
+fn is_deleted(e: &BytesStart) -> bool {
+    has_attribute(e, b"deleted")
+}
+
+/// Returns true if an element has an attribute with the given name.
+fn has_attribute(e: &BytesStart, name: &[u8]) -> bool {
+    e.attributes()
+        .flatten()
+        .any(|attribute| attribute.key.as_ref() == name)
+}
@@ -0,0 +1,258 @@
+//! Renderers which turn parsed wiki text into the `text` of a revision.
+//!
+//! The parser uses [PlainTextRenderer](struct.PlainTextRenderer.html) unless
+//! another renderer is given with
+//! [Parser::renderer](../struct.Parser.html#method.renderer). Custom renderers
+//! usually handle a few kinds of nodes themselves, and pass everything else
+//! on to [render_plain_text](fn.render_plain_text.html).
+//!
+//! ## Example
+//! ```rust
+//! use parse_wiki_text::Node;
+//! use wikidump::render::{render_plain_text, Output, Renderer};
+//! use wikidump::Parser;
+//!
+//! /// Keeps the bullets of unordered lists.
+//! struct Bullets;
+//!
+//! impl Renderer for Bullets {
+//!     fn render_node(&self, node: &Node, output: &mut Output) {
+//!         match node {
+//!             Node::UnorderedList { items, .. } => {
+//!                 for item in items {
+//!                     output.push_str("\n* ");
+//!                     self.render_nodes(&item.nodes, output);
+//!                 }
+//!             }
+//!             _ => render_plain_text(self, node, output),
+//!         }
+//!     }
+//! }
+//!
+//! let parser = Parser::new().renderer(Bullets);
+//! let site = parser.parse_file("tests/simplewiki.xml").unwrap();
+//! ```
+
+use crate::{normalize_title, LinkSpan, OffsetMap};
+use parse_wiki_text::Node;
+use std::ops::Range;
+
+/// The text which has been rendered so far.
+#[derive(Debug, Default)]
+pub struct Output {
+    text: String,
+    /// The nodes which are being rendered, innermost last.
+    nodes: Vec<OpenNode>,
+    offsets: Option<OffsetMap>,
+    links: Vec<LinkSpan>,
+}
+
+/// A node which is being rendered.
+#[derive(Debug)]
+struct OpenNode {
+    /// The range of wiki text of the node.
+    raw: Range<usize>,
+    /// The index of the node in `links`, if it is a link.
+    link: Option<usize>,
+}
+
+impl Output {
+    pub(crate) fn new(capacity: usize, map_offsets: bool) -> Output {
+        Output {
+            text: String::with_capacity(capacity),
+            nodes: Vec::new(),
+            offsets: Some(OffsetMap::default()).filter(|_| map_offsets),
+            links: Vec::new(),
+        }
+    }
+
+    /// Appends a string to the text. Its offsets are mapped to the whole node
+    /// which is being rendered.
+    pub fn push_str(&mut self, string: &str) {
+        let start = self.text.len();
+        self.text.push_str(string);
+
+        if let Some(offsets) = self.offsets.as_mut() {
+            let raw = self.nodes.last().map(|n| n.raw.clone()).unwrap_or_default();
+            offsets.push(start..self.text.len(), raw, false);
+        }
+    }
+
+    /// Appends a character to the text, like [push_str](#method.push_str).
+    pub fn push(&mut self, character: char) {
+        self.push_str(character.encode_utf8(&mut [0; 4]));
+    }
+
+    /// Appends a string which was copied as-is from the wiki text, starting
+    /// at `raw_start`, so that its offsets can be mapped exactly.
+    pub fn push_raw(&mut self, string: &str, raw_start: usize) {
+        let start = self.text.len();
+        self.text.push_str(string);
+
+        if let Some(offsets) = self.offsets.as_mut() {
+            offsets.push(
+                start..self.text.len(),
+                raw_start..raw_start + string.len(),
+                true,
+            );
+        }
+    }
+
+    /// Marks the start of rendering a node, so that any text which is not
+    /// copied from the wiki text is mapped to this node, and so that the text
+    /// of links can be found. This is done by
+    /// [Renderer::render_nodes](trait.Renderer.html#method.render_nodes).
+    pub fn start_node(&mut self, node: &Node) {
+        let link = match node {
+            Node::Link { target, .. } => {
+                let start = self.text.len();
+                self.links.push(LinkSpan {
+                    start,
+                    end: start,
+                    target: target.trim().to_string(),
+                    normalized_target: normalize_title(target),
+                });
+
+                Some(self.links.len() - 1)
+            }
+            _ => None,
+        };
+
+        self.nodes.push(OpenNode {
+            raw: node_range(node),
+            link,
+        });
+    }
+
+    /// Marks the end of rendering the node which was started last.
+    pub fn end_node(&mut self) {
+        if let Some(index) = self.nodes.pop().and_then(|n| n.link) {
+            self.links[index].end = self.text.len();
+        }
+    }
+
+    /// Returns the text which has been rendered so far.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub(crate) fn into_parts(self) -> (String, Option<OffsetMap>, Vec<LinkSpan>) {
+        (self.text, self.offsets, self.links)
+    }
+}
+
+/// Turns the nodes of parsed wiki text into text.
+///
+/// Renderers are shared by every thread which processes pages, so they must
+/// be `Send` and `Sync`.
+pub trait Renderer: Send + Sync {
+    /// Renders a node, including any nodes it contains. Implementations
+    /// should render contained nodes with
+    /// [render_nodes](#method.render_nodes), so that they are rendered by
+    /// this renderer as well.
+    fn render_node(&self, node: &Node, output: &mut Output);
+
+    /// Renders a list of nodes, one after another.
+    fn render_nodes(&self, nodes: &[Node], output: &mut Output) {
+        nodes.iter().for_each(|node| {
+            output.start_node(node);
+            self.render_node(node, output);
+            output.end_node();
+        });
+    }
+}
+
+/// Renders text which could be read naturally, by keeping the text of
+/// paragraphs, headings, links and lists, and leaving out markup, templates,
+/// tables, images and categories. This is the default renderer.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PlainTextRenderer;
+
+impl Renderer for PlainTextRenderer {
+    fn render_node(&self, node: &Node, output: &mut Output) {
+        render_plain_text(self, node, output)
+    }
+}
+
+/// Renders a node the same way as
+/// [PlainTextRenderer](struct.PlainTextRenderer.html), but renders any nodes
+/// it contains with `renderer`. This lets custom renderers fall back to the
+/// default for the nodes they do not handle themselves.
+pub fn render_plain_text<R>(renderer: &R, node: &Node, output: &mut Output)
+where
+    R: Renderer + ?Sized,
+{
+    match node {
+        Node::Text { value, start, .. } => output.push_raw(value, *start),
+        Node::ParagraphBreak { .. } => output.push('\n'),
+        Node::CharacterEntity { character, .. } => output.push(*character),
+        Node::Link { text, .. } => renderer.render_nodes(text, output),
+        Node::ExternalLink { nodes, .. } => renderer.render_nodes(nodes, output),
+        Node::Heading { nodes, .. } => {
+            output.push('\n');
+            renderer.render_nodes(nodes, output);
+            output.push('\n');
+        }
+        Node::Image { .. } => {
+            // @TODO @Completeness: Allow image text.
+            // Currently not allowed because it's a bit difficult to figure
+            // out what is normal text and what isn't.
+        }
+        Node::OrderedList { items, .. } | Node::UnorderedList { items, .. } => {
+            items
+                .iter()
+                .for_each(|i| renderer.render_nodes(&i.nodes, output));
+        }
+        Node::DefinitionList { items, .. } => {
+            items
+                .iter()
+                .for_each(|i| renderer.render_nodes(&i.nodes, output));
+        }
+        Node::Preformatted { nodes, .. } => renderer.render_nodes(nodes, output),
+        Node::Template { .. }
+        | Node::Bold { .. }
+        | Node::BoldItalic { .. }
+        | Node::HorizontalDivider { .. }
+        | Node::MagicWord { .. }
+        | Node::Italic { .. }
+        | Node::Redirect { .. }
+        | Node::Comment { .. }
+        | Node::Tag { .. }
+        | Node::StartTag { .. }
+        | Node::EndTag { .. }
+        | Node::Parameter { .. }
+        | Node::Category { .. }
+        | Node::Table { .. } => {}
+    }
+}
+
+/// Returns the range of wiki text which a node was parsed from.
+fn node_range(node: &Node) -> Range<usize> {
+    match *node {
+        Node::Bold { start, end }
+        | Node::BoldItalic { start, end }
+        | Node::Category { start, end, .. }
+        | Node::CharacterEntity { start, end, .. }
+        | Node::Comment { start, end }
+        | Node::DefinitionList { start, end, .. }
+        | Node::EndTag { start, end, .. }
+        | Node::ExternalLink { start, end, .. }
+        | Node::Heading { start, end, .. }
+        | Node::HorizontalDivider { start, end }
+        | Node::Image { start, end, .. }
+        | Node::Italic { start, end }
+        | Node::Link { start, end, .. }
+        | Node::MagicWord { start, end }
+        | Node::OrderedList { start, end, .. }
+        | Node::ParagraphBreak { start, end }
+        | Node::Parameter { start, end, .. }
+        | Node::Preformatted { start, end, .. }
+        | Node::Redirect { start, end, .. }
+        | Node::StartTag { start, end, .. }
+        | Node::Table { start, end, .. }
+        | Node::Tag { start, end, .. }
+        | Node::Template { start, end, .. }
+        | Node::Text { start, end, .. }
+        | Node::UnorderedList { start, end, .. } => start..end,
+    }
+}
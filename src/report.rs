@@ -0,0 +1,134 @@
+use crate::{OverBudgetRevision, Page, PageRevision, TextContent};
+use parse_wiki_text::Warning;
+use std::collections::BTreeMap;
+
+/// A problem which was found while parsing the wiki text of a revision, such
+/// as a link which is not closed. The text of a revision with warnings may
+/// not have been extracted the way it was intended.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ParseWarning {
+    /// The byte offset in the raw wiki text where the problem starts.
+    pub start: usize,
+    /// The byte offset in the raw wiki text where the problem ends.
+    pub end: usize,
+    /// The kind of problem, e.g., "InvalidLinkSyntax".
+    pub kind: String,
+    /// A description of the problem, e.g., "Invalid link syntax.".
+    pub message: String,
+}
+
+impl From<&Warning> for ParseWarning {
+    fn from(warning: &Warning) -> Self {
+        ParseWarning {
+            start: warning.start,
+            end: warning.end,
+            kind: format!("{:?}", warning.message),
+            message: warning.message.message().to_string(),
+        }
+    }
+}
+
+/// A page which could not be read because the XML of the dump is malformed,
+/// and which was skipped by a
+/// [lenient](struct.Parser.html#method.lenient) parser.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FailedPage {
+    /// The title of the page, if it was read before the problem was found.
+    pub title: Option<String>,
+    /// The byte offset in the (uncompressed) dump where the problem was
+    /// found.
+    pub position: u64,
+    /// A description of the problem.
+    pub error: String,
+}
+
+/// A summary of how well the wiki text of a dump could be parsed, which can
+/// be used to find malformed wiki text.
+///
+/// # Example
+/// ```rust
+/// use wikidump::Parser;
+///
+/// let parser = Parser::new();
+/// let site = parser.parse_file("tests/simplewiki.xml").unwrap();
+///
+/// for (kind, count) in &site.report.warnings {
+///     println!("{}: {}", kind, count);
+/// }
+///
+/// for title in &site.report.pages_with_warnings {
+///     println!("Check {}", title);
+/// }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ParseReport {
+    /// The number of revisions whose wiki text was processed, not counting
+    /// revisions which were over the processing limits of the parser.
+    pub processed_revisions: u64,
+    /// The number of warnings, by kind.
+    pub warnings: BTreeMap<String, u64>,
+    /// The titles of the pages which have a warning in any of their
+    /// revisions, in the order they were read.
+    pub pages_with_warnings: Vec<String>,
+    /// The revisions which were over the processing limits of the parser, in
+    /// the order they were read.
+    pub over_budget: Vec<OverBudgetRevision>,
+    /// The number of revisions over the time budget whose wiki text was
+    /// still being processed in the background when the report was last
+    /// brought up to date. See
+    /// [time_budget](struct.Parser.html#method.time_budget).
+    pub running_over_budget: usize,
+    /// The pages which were skipped because they could not be read, in the
+    /// order they were found. This is only ever filled in by a
+    /// [lenient](struct.Parser.html#method.lenient) parser.
+    pub failed_pages: Vec<FailedPage>,
+}
+
+impl ParseReport {
+    /// Returns the number of warnings of all kinds.
+    pub fn total_warnings(&self) -> u64 {
+        self.warnings.values().sum()
+    }
+
+    /// Adds the revisions of a page which has been processed to the report.
+    pub(crate) fn add_page(&mut self, page: &Page, processed: bool) {
+        for revision in &page.revisions {
+            self.add_revision(&page.title, revision, processed);
+        }
+    }
+
+    /// Adds a revision which has been processed to the report, given the
+    /// title of its page.
+    pub(crate) fn add_revision(&mut self, title: &str, revision: &PageRevision, processed: bool) {
+        if processed
+            && revision.text_content == TextContent::Included
+            && revision.is_wiki_text()
+            && revision.budget_exceeded.is_none()
+        {
+            self.processed_revisions += 1;
+        }
+
+        if let Some(reason) = &revision.budget_exceeded {
+            self.over_budget.push(OverBudgetRevision {
+                title: title.to_string(),
+                revision_id: revision.id,
+                reason: reason.clone(),
+            });
+        }
+
+        for warning in &revision.warnings {
+            *self.warnings.entry(warning.kind.clone()).or_default() += 1;
+        }
+
+        // The revisions of a page are added one after another, so the page
+        // is only listed once.
+        if !revision.warnings.is_empty()
+            && self.pages_with_warnings.last().map(String::as_str) != Some(title)
+        {
+            self.pages_with_warnings.push(title.to_string());
+        }
+    }
+}
@@ -0,0 +1,118 @@
+use crate::PageRevision;
+use std::collections::HashSet;
+
+/// Which revisions of each page the parser keeps, for dumps which include
+/// the full history of each page, like the `pages-meta-history` dumps of
+/// Wikipedia.
+///
+/// Revisions are selected while the dump is read, so the text of revisions
+/// which are not selected is skipped rather than stored, and
+/// [Page::revisions](struct.Page.html#structfield.revisions) only ever holds
+/// the selected revisions. Visitors are only passed the selected revisions
+/// as well.
+///
+/// See [Parser::select_revisions](struct.Parser.html#method.select_revisions).
+///
+/// # Example
+/// ```rust
+/// use wikidump::{Parser, RevisionSelection};
+///
+/// let parser = Parser::new()
+///     .exclude_pages(false)
+///     .select_revisions(RevisionSelection::Latest);
+/// let site = parser.parse_file("tests/simplewiki-history.xml").unwrap();
+///
+/// assert!(site.pages.iter().all(|p| p.revisions.len() == 1));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum RevisionSelection {
+    /// Keep every revision.
+    #[default]
+    All,
+    /// Keep only the last revision of each page. Every revision is still
+    /// read, since the last one is only known once the page ends, but each
+    /// replaces the one before it. Visitors are passed the last revision
+    /// once the page ends.
+    Latest,
+    /// Keep only the first revision of each page.
+    First,
+    /// Keep the first revision of each page and every `n`th revision after
+    /// it, e.g., the 1st, 11th, 21st and so on for 10.
+    EveryNth(usize),
+    /// Keep the revisions which were made from `from` (inclusive) until
+    /// `until` (exclusive). Both are timestamps in the ISO 8601 format of
+    /// the dump, e.g., "2019-04-26T03:40:24Z", and may be shortened, e.g.,
+    /// to "2019" or "2019-04". Revisions without a timestamp are not kept.
+    Between {
+        from: Option<String>,
+        until: Option<String>,
+    },
+    /// Keep the revisions made by one of the given users, by their username,
+    /// or IP address for unregistered users.
+    Contributors(HashSet<String>),
+}
+
+impl RevisionSelection {
+    /// Creates a selection of the revisions made within a range of time.
+    ///
+    /// See [RevisionSelection::Between](enum.RevisionSelection.html#variant.Between).
+    pub fn between(from: Option<&str>, until: Option<&str>) -> RevisionSelection {
+        RevisionSelection::Between {
+            from: from.map(str::to_string),
+            until: until.map(str::to_string),
+        }
+    }
+
+    /// Creates a selection of the revisions made by the given users.
+    ///
+    /// See [RevisionSelection::Contributors](enum.RevisionSelection.html#variant.Contributors).
+    pub fn contributors<I, S>(names: I) -> RevisionSelection
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        RevisionSelection::Contributors(names.into_iter().map(Into::into).collect())
+    }
+
+    /// Returns false if the revision with the given index in its page cannot
+    /// be selected, before any of it has been read.
+    pub(crate) fn may_select(&self, index: usize) -> bool {
+        match self {
+            RevisionSelection::First => index == 0,
+            // `usize::is_multiple_of` needs Rust 1.87.
+            #[allow(clippy::manual_is_multiple_of)]
+            RevisionSelection::EveryNth(n) => index % (*n).max(1) == 0,
+            _ => true,
+        }
+    }
+
+    /// Returns true if the revision with the given index in its page is
+    /// selected. The revision only needs to have been read up to its text.
+    pub(crate) fn selects(&self, index: usize, revision: &PageRevision) -> bool {
+        if !self.may_select(index) {
+            return false;
+        }
+
+        match self {
+            RevisionSelection::Between { from, until } => {
+                let timestamp = revision.timestamp.as_str();
+
+                let after_from = match from.as_deref() {
+                    Some(from) => timestamp >= from,
+                    None => true,
+                };
+                let before_until = match until.as_deref() {
+                    Some(until) => timestamp < until,
+                    None => true,
+                };
+
+                !timestamp.is_empty() && after_from && before_until
+            }
+            RevisionSelection::Contributors(names) => revision
+                .contributor
+                .name()
+                .is_some_and(|name| names.contains(name)),
+            _ => true,
+        }
+    }
+}
@@ -0,0 +1,294 @@
+use crate::reader::DumpReader;
+use crate::{
+    open_dump, Checkpoint, Error, Exception, Page, PageContext, PageRevision, ParseReport, Parser,
+    Site,
+};
+use std::io::BufRead;
+use std::path::{Path, PathBuf};
+
+/// A continuous stream of pages read from the parts of a split dump, as if
+/// they were a single dump file. Pages are read lazily, one part at a time,
+/// so the whole dump never needs to fit in memory. Streams are `Send`, so
+/// they can be moved to a worker thread.
+///
+/// See [stream_files](struct.Parser.html#method.stream_files).
+pub struct PageStream<'p> {
+    parser: &'p Parser,
+    site: Site,
+    /// If false, the wiki text of each page is left for the caller to process.
+    process: bool,
+    parts: std::vec::IntoIter<PathBuf>,
+    /// The index of the part which is being read.
+    part: usize,
+    current: Option<DumpReader<Box<dyn BufRead + Send>>>,
+    report: ParseReport,
+    /// The checkpoint after the last page which was yielded.
+    checkpoint: Option<Checkpoint>,
+}
+
+impl<'p> PageStream<'p> {
+    pub(crate) fn new<I, P>(
+        parser: &'p Parser,
+        parts: I,
+        process: bool,
+    ) -> Result<PageStream<'p>, Exception>
+    where
+        I: IntoIterator<Item = P>,
+        P: AsRef<Path>,
+    {
+        let mut parts = parts
+            .into_iter()
+            .map(|p| p.as_ref().to_path_buf())
+            .collect::<Vec<_>>();
+
+        if parts.is_empty() {
+            return Err(Error::NoParts.into());
+        }
+
+        let resume = parser.resume_from.as_ref();
+        let part = resume.map_or(0, |c| c.part);
+
+        if part >= parts.len() {
+            return Err(Error::InvalidCheckpoint.into());
+        }
+
+        // The progress is counted against the size of all parts together,
+        // including any parts before the checkpoint.
+        let sizes = parts
+            .iter()
+            .map(|p| std::fs::metadata(p).ok().map(|m| m.len()))
+            .collect::<Vec<_>>();
+        let mut parts = parts.split_off(part).into_iter();
+        let first = parts.next().ok_or(Error::NoParts)?;
+
+        let mut site = Site::new();
+        let mut reader = open_dump(&first, parser.strict, None)?;
+        reader.read_site_info(&mut site)?;
+
+        if let Some(checkpoint) = resume {
+            reader = open_dump(&first, parser.strict, Some(checkpoint))?;
+        }
+
+        reader.progress.set_file_size(sizes.iter().copied().sum());
+        reader
+            .progress
+            .skip_file_bytes(sizes[..part].iter().flatten().sum());
+
+        Ok(PageStream {
+            parser,
+            site,
+            process,
+            parts,
+            part,
+            current: Some(reader),
+            report: ParseReport::default(),
+            checkpoint: resume.cloned(),
+        })
+    }
+
+    /// Returns the site information of the dump. The list of pages is always
+    /// empty, since the pages are yielded by the stream instead.
+    pub fn site(&self) -> &Site {
+        &self.site
+    }
+
+    /// Returns a summary of how well the wiki text of the pages which have
+    /// been yielded so far could be parsed, and of the pages which have been
+    /// skipped so far by a lenient parser.
+    pub fn report(&self) -> &ParseReport {
+        &self.report
+    }
+
+    /// Returns a checkpoint right after the last page which was yielded,
+    /// from which a later stream can carry on with
+    /// [Parser::resume_from](struct.Parser.html#method.resume_from). This is
+    /// `None` until the first page has been yielded, unless the stream
+    /// itself resumed from a checkpoint.
+    pub fn checkpoint(&self) -> Option<&Checkpoint> {
+        self.checkpoint.as_ref()
+    }
+
+    /// Opens the next part, checking that it belongs to the same site.
+    fn open_next_part(&mut self) -> Result<(), Exception> {
+        let previous = self.current.take();
+
+        if let Some(path) = self.parts.next() {
+            self.part += 1;
+
+            let mut site = Site::new();
+            let mut reader = open_dump(&path, self.parser.strict, None)?;
+
+            if let Some(previous) = previous.as_ref() {
+                reader.continue_from(previous);
+            }
+
+            reader.read_site_info(&mut site)?;
+            self.site.check_same_site(&site, &path)?;
+            self.current = Some(reader);
+        }
+
+        Ok(())
+    }
+}
+
+impl Iterator for PageStream<'_> {
+    type Item = Result<Page, Exception>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut page = Page::new();
+
+        while let Some(reader) = self.current.as_mut() {
+            match reader.next_page(self.parser, &mut page, None, &mut self.report) {
+                Ok(true) => {
+                    let stream = reader.stream_start();
+                    self.checkpoint = Some(Checkpoint {
+                        part: self.part,
+                        page_id: page.id,
+                        position: reader.position(),
+                        stream_offset: stream.offset,
+                        stream_position: stream.position,
+                    });
+
+                    if self.process {
+                        self.parser.process_page(&mut page);
+
+                        if let Err(e) = self.parser.check_cancelled() {
+                            self.current = None;
+                            return Some(Err(e.into()));
+                        }
+
+                        self.report.add_page(&page, self.parser.process_wiki_text);
+                        self.parser.update_report(&mut self.report);
+                    }
+
+                    return Some(Ok(page));
+                }
+                Ok(false) if reader.limit_reached(self.parser) => self.current = None,
+                Ok(false) => {
+                    if let Err(e) = self.open_next_part() {
+                        return Some(Err(e));
+                    }
+                }
+                Err(e) => {
+                    self.current = None;
+                    return Some(Err(e));
+                }
+            }
+        }
+
+        None
+    }
+}
+
+/// A stream of the revisions of a dump, which are read one at a time rather
+/// than a page at a time, so that pages with many revisions never need to fit
+/// in memory. Each revision is yielded along with the page it belongs to.
+///
+/// See [stream_revisions](struct.Parser.html#method.stream_revisions).
+pub struct RevisionStream<'p> {
+    pages: PageStream<'p>,
+    /// The page of the last revision which was yielded, without any of its
+    /// revisions.
+    page: Page,
+}
+
+impl<'p> RevisionStream<'p> {
+    pub(crate) fn new<I, P>(parser: &'p Parser, parts: I) -> Result<RevisionStream<'p>, Exception>
+    where
+        I: IntoIterator<Item = P>,
+        P: AsRef<Path>,
+    {
+        Ok(RevisionStream {
+            pages: PageStream::new(parser, parts, false)?,
+            page: Page::new(),
+        })
+    }
+
+    /// Returns the site information of the dump.
+    pub fn site(&self) -> &Site {
+        self.pages.site()
+    }
+
+    /// Returns a summary of how well the wiki text of the revisions which
+    /// have been yielded so far could be parsed, and of the pages which have
+    /// been skipped so far by a lenient parser.
+    pub fn report(&self) -> &ParseReport {
+        self.pages.report()
+    }
+}
+
+impl Iterator for RevisionStream<'_> {
+    type Item = Result<(PageContext, PageRevision), Exception>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let pages = &mut self.pages;
+        let parser = pages.parser;
+
+        while let Some(reader) = pages.current.as_mut() {
+            match reader.next_revision(parser, &mut self.page, &mut pages.report) {
+                Ok(Some(mut revision)) => {
+                    parser.process_revision(&mut revision);
+
+                    if let Err(e) = parser.check_cancelled() {
+                        pages.current = None;
+                        return Some(Err(e.into()));
+                    }
+
+                    pages.report.add_revision(
+                        &self.page.title,
+                        &revision,
+                        parser.process_wiki_text,
+                    );
+                    parser.update_report(&mut pages.report);
+
+                    return Some(Ok((PageContext::from(&self.page), revision)));
+                }
+                Ok(None) if reader.limit_reached(parser) => pages.current = None,
+                Ok(None) => {
+                    if let Err(e) = pages.open_next_part() {
+                        return Some(Err(e));
+                    }
+                }
+                Err(e) => {
+                    pages.current = None;
+                    return Some(Err(e));
+                }
+            }
+        }
+
+        None
+    }
+}
+
+/// Returns the part files of a split dump which match a glob pattern, such
+/// as `"enwiki-latest-pages-articles*.xml-p*.bz2"`, in dump order.
+///
+/// Part files are ordered by the page range in their name (e.g.,
+/// `-p41243p151573`) if there is one, so that part 10 follows part 9 rather
+/// than part 1.
+///
+/// # Example
+/// ```rust
+/// use wikidump::{dump_parts, Parser};
+///
+/// let parts = dump_parts("tests/parts/simplewiki-pages-articles*").unwrap();
+/// let site = Parser::new().parse_files(parts).unwrap();
+/// ```
+pub fn dump_parts(pattern: &str) -> Result<Vec<PathBuf>, Exception> {
+    let mut parts = glob::glob(pattern)?.collect::<Result<Vec<_>, _>>()?;
+    parts.sort_by_cached_key(|p| (first_page_id(p), p.clone()));
+
+    Ok(parts)
+}
+
+/// Reads the first page ID out of a part file name like
+/// `enwiki-latest-pages-articles2.xml-p41243p151573.bz2`.
+fn first_page_id(path: &Path) -> Option<u64> {
+    let name = path.file_name()?.to_str()?;
+    let range = &name[name.rfind("-p")? + 2..];
+    let digits = range
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(range.len());
+
+    range[..digits].parse().ok()
+}
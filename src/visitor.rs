@@ -0,0 +1,85 @@
+use crate::{FailedPage, Page, PageRevision, Site};
+
+/// What the parser should do after a [PageVisitor](trait.PageVisitor.html)
+/// has been called.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Visit {
+    /// Keep reading the dump as usual.
+    Continue,
+    /// Skip the rest of the current page, without reading its remaining
+    /// revisions. The page is not passed to
+    /// [on_page_end](trait.PageVisitor.html#method.on_page_end).
+    Skip,
+    /// Stop reading the dump altogether.
+    Stop,
+}
+
+/// Receives the contents of a dump while it is being read, so that custom
+/// data can be extracted without keeping every page in memory, and so that
+/// parsing can end early.
+///
+/// Every method has a default implementation which continues, so only the
+/// events of interest need to be implemented. Pages which are excluded by
+/// the parser are never visited.
+///
+/// See [visit_file](struct.Parser.html#method.visit_file).
+///
+/// # Example
+/// ```rust
+/// use wikidump::{Page, PageVisitor, Parser, Visit};
+///
+/// /// Collects the titles of the first few pages.
+/// struct Titles(Vec<String>);
+///
+/// impl PageVisitor for Titles {
+///     fn on_page_start(&mut self, page: &Page) -> Visit {
+///         self.0.push(page.title.clone());
+///
+///         if self.0.len() == 3 {
+///             Visit::Stop
+///         } else {
+///             // The revisions are not needed, so save time by skipping them
+///             Visit::Skip
+///         }
+///     }
+/// }
+///
+/// let mut titles = Titles(vec![]);
+/// Parser::new()
+///     .visit_file("tests/simplewiki.xml", &mut titles)
+///     .unwrap();
+///
+/// assert_eq!(titles.0, ["April", "August", "Art"]);
+/// ```
+pub trait PageVisitor {
+    /// Called once the siteinfo header of the dump has been read, before any
+    /// pages. The list of pages of the site is always empty.
+    fn on_siteinfo(&mut self, _site: &Site) -> Visit {
+        Visit::Continue
+    }
+
+    /// Called once the title, namespace and ID of a page are known, before
+    /// any of its revisions are read.
+    fn on_page_start(&mut self, _page: &Page) -> Visit {
+        Visit::Continue
+    }
+
+    /// Called for each revision of a page once it has been read and
+    /// processed. `page` holds the revisions which were read before this one.
+    fn on_revision(&mut self, _page: &Page, _revision: &PageRevision) -> Visit {
+        Visit::Continue
+    }
+
+    /// Called once all revisions of a page have been read and processed.
+    fn on_page_end(&mut self, _page: &Page) -> Visit {
+        Visit::Continue
+    }
+
+    /// Called when a [lenient](struct.Parser.html#method.lenient) parser
+    /// skips a page because it could not be read. The page is not passed to
+    /// [on_page_end](#method.on_page_end), even if it was started. Returning
+    /// [Visit::Skip](enum.Visit.html#variant.Skip) is the same as continuing.
+    fn on_failed_page(&mut self, _page: &FailedPage) -> Visit {
+        Visit::Continue
+    }
+}
@@ -0,0 +1,598 @@
+#[cfg(all(test, feature = "json"))]
+mod jsonl {
+    use serde_json::{json, Value};
+    use wikidump::export::{Field, JsonlWriter};
+    use wikidump::Parser;
+
+    fn read_lines(output: Vec<u8>) -> Vec<Value> {
+        String::from_utf8(output)
+            .expect("Output is not UTF-8")
+            .lines()
+            .map(|line| serde_json::from_str(line).expect("Line is not JSON"))
+            .collect()
+    }
+
+    #[test]
+    fn can_write_one_line_per_page() {
+        let parser = Parser::new();
+        let site = parser
+            .parse_file("tests/simplewiki.xml")
+            .expect("Could not parse simplewiki dump");
+
+        let mut writer = JsonlWriter::new(Vec::new());
+        writer.write_site(&site).expect("Could not write pages");
+
+        let lines = read_lines(writer.into_inner());
+
+        assert_eq!(lines.len(), site.pages.len());
+        assert_eq!(lines[2]["title"], "Art");
+        assert_eq!(lines[2]["id"], 6);
+        assert_eq!(lines[2]["ns"], 0);
+        assert_eq!(
+            lines[2]["revisions"][0]["text"],
+            site.pages[2].revisions[0].text
+        );
+    }
+
+    #[test]
+    fn can_write_one_line_per_revision() {
+        let parser = Parser::new();
+        let site = parser
+            .parse_file("tests/simplewiki-history.xml")
+            .expect("Could not parse simplewiki history dump");
+
+        let mut writer = JsonlWriter::new(Vec::new())
+            .fields(&[
+                Field::Title,
+                Field::RevisionId,
+                Field::ParentId,
+                Field::Contributor,
+                Field::Comment,
+                Field::Text,
+            ])
+            .per_revision(true);
+        writer.write_site(&site).expect("Could not write pages");
+
+        let lines = read_lines(writer.into_inner());
+
+        assert_eq!(lines.len(), 4);
+        assert_eq!(
+            lines[0],
+            json!({
+                "title": "Moon",
+                "revision_id": 19031,
+                "parent_id": null,
+                "contributor": "Angela",
+                "comment": "new page",
+                "text": "The Moon is the only natural satellite of the Earth.",
+            })
+        );
+        assert_eq!(lines[2]["contributor"], Value::Null);
+        assert_eq!(lines[2]["comment"], Value::Null);
+        assert_eq!(lines[2]["text"], Value::Null);
+    }
+
+    #[test]
+    fn can_write_categories() {
+        let parser = Parser::new();
+        let site = parser
+            .parse_file("tests/simplewiki.xml")
+            .expect("Could not parse simplewiki dump");
+
+        let mut writer = JsonlWriter::new(Vec::new())
+            .fields(&[Field::Title, Field::Categories])
+            .per_revision(true);
+        writer.write_site(&site).expect("Could not write pages");
+
+        let lines = read_lines(writer.into_inner());
+        let art = lines
+            .iter()
+            .find(|line| line["title"] == "Art")
+            .expect("Could not find example page");
+
+        assert_eq!(art.as_object().unwrap().len(), 2);
+        assert!(art["categories"]
+            .as_array()
+            .unwrap()
+            .contains(&Value::from("Art")));
+    }
+
+    #[test]
+    fn can_write_page_fields_only() {
+        let parser = Parser::new();
+        let site = parser
+            .parse_file("tests/simplewiki.xml")
+            .expect("Could not parse simplewiki dump");
+
+        let mut writer = JsonlWriter::new(Vec::new()).fields(&[Field::Id, Field::Title]);
+        writer
+            .write_page(&site.pages[0])
+            .expect("Could not write page");
+
+        let output = String::from_utf8(writer.into_inner()).unwrap();
+
+        assert_eq!(output, "{\"id\":1,\"title\":\"April\"}\n");
+    }
+}
+
+#[cfg(all(test, feature = "arrow"))]
+mod parquet {
+    use arrow_array::cast::AsArray;
+    use arrow_array::types::{TimestampSecondType, UInt64Type};
+    use arrow_array::Array;
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+    use std::fs::File;
+    use wikidump::export::{BatchBuilder, ParquetWriter};
+    use wikidump::Parser;
+
+    #[test]
+    fn can_build_record_batches() {
+        let parser = Parser::new();
+        let site = parser
+            .parse_file("tests/simplewiki-history.xml")
+            .expect("Could not parse simplewiki history dump");
+
+        let mut builder = BatchBuilder::new();
+        builder.push_site(&site);
+
+        assert_eq!(builder.len(), 4);
+
+        let batch = builder.finish();
+
+        assert!(builder.is_empty());
+        assert_eq!(batch.schema(), BatchBuilder::schema());
+        assert_eq!(batch.num_rows(), 4);
+
+        let revision_ids = batch.column(3).as_primitive::<UInt64Type>();
+        let timestamps = batch.column(4).as_primitive::<TimestampSecondType>();
+        let texts = batch.column(5).as_string::<i32>();
+
+        assert_eq!(revision_ids.value(0), 19031);
+        // 2004-02-11T20:45:12Z
+        assert_eq!(timestamps.value(0), 1076532312);
+        assert_eq!(
+            texts.value(0),
+            "The Moon is the only natural satellite of the Earth."
+        );
+        assert!(texts.is_null(2));
+    }
+
+    #[test]
+    fn can_write_parquet_file() {
+        let parser = Parser::new().exclude_pages(false);
+        let site = parser
+            .parse_file("tests/simplewiki-history.xml")
+            .expect("Could not parse simplewiki history dump");
+
+        let path = std::env::temp_dir().join("wikidump-can-write-parquet-file.parquet");
+        let file = File::create(&path).expect("Could not create parquet file");
+
+        let mut writer =
+            ParquetWriter::with_row_group_size(file, 2).expect("Could not create parquet writer");
+        writer.write_site(&site).expect("Could not write pages");
+        writer.close().expect("Could not close parquet writer");
+
+        let reader = ParquetRecordBatchReaderBuilder::try_new(
+            File::open(&path).expect("Could not open parquet file"),
+        )
+        .expect("Could not read parquet file");
+
+        assert_eq!(reader.metadata().num_row_groups(), 3);
+        assert_eq!(reader.schema(), &BatchBuilder::schema());
+
+        let rows = reader
+            .build()
+            .expect("Could not read parquet file")
+            .map(|batch| batch.expect("Could not read batch").num_rows())
+            .sum::<usize>();
+
+        assert_eq!(rows, 6);
+    }
+}
+
+#[cfg(all(test, feature = "sqlite"))]
+mod sqlite {
+    use wikidump::export::SqliteWriter;
+    use wikidump::Parser;
+
+    #[test]
+    fn can_write_pages_and_revisions() {
+        let parser = Parser::new();
+        let site = parser
+            .parse_file("tests/simplewiki-history.xml")
+            .expect("Could not parse simplewiki history dump");
+
+        let mut writer = SqliteWriter::open_in_memory().expect("Could not create database");
+        writer.write_site(&site).expect("Could not write pages");
+        let connection = writer.finish().expect("Could not commit pages");
+
+        let dbname: String = connection
+            .query_row("SELECT dbname FROM site", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(dbname, "simplewiki");
+
+        let (id, ns, title): (u64, i32, String) = connection
+            .query_row("SELECT id, ns, title FROM pages", [], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+            })
+            .unwrap();
+        assert_eq!((id, ns, title.as_str()), (2412, 0, "Moon"));
+
+        let revisions: u64 = connection
+            .query_row(
+                "SELECT COUNT(*) FROM revisions WHERE page_id = 2412",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(revisions, 4);
+
+        let (contributor, text): (Option<String>, Option<String>) = connection
+            .query_row(
+                "SELECT contributor, text FROM revisions WHERE id = 20611",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!(contributor, None);
+        assert_eq!(text, None);
+    }
+
+    #[test]
+    fn can_write_links_and_categories() {
+        let parser = Parser::new();
+        let site = parser
+            .parse_file("tests/simplewiki.xml")
+            .expect("Could not parse simplewiki dump");
+
+        let mut writer = SqliteWriter::open_in_memory().expect("Could not create database");
+        writer.write_site(&site).expect("Could not write pages");
+        let connection = writer.finish().expect("Could not commit pages");
+
+        let linking: u64 = connection
+            .query_row(
+                "SELECT COUNT(DISTINCT revision_id) FROM links WHERE target = 'March'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert!(linking > 0);
+
+        let title: String = connection
+            .query_row(
+                "SELECT title FROM pages
+                 JOIN revisions ON revisions.page_id = pages.id
+                 JOIN categories ON categories.revision_id = revisions.id
+                 WHERE categories.name = 'Art'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(title, "Art");
+    }
+
+    #[test]
+    fn can_search_text() {
+        let parser = Parser::new();
+        let site = parser
+            .parse_file("tests/simplewiki-history.xml")
+            .expect("Could not parse simplewiki history dump");
+
+        let mut writer = SqliteWriter::open_in_memory()
+            .expect("Could not create database")
+            .batch_size(1);
+        writer.write_site(&site).expect("Could not write pages");
+        let connection = writer.finish().expect("Could not commit pages");
+
+        let mut statement = connection
+            .prepare(
+                "SELECT rowid FROM revisions_fts WHERE revisions_fts MATCH 'orbit' ORDER BY rowid",
+            )
+            .unwrap();
+        let ids = statement
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<Vec<u64>, _>>()
+            .unwrap();
+
+        assert_eq!(ids, vec![20544, 20612]);
+    }
+
+    #[test]
+    fn can_write_pages_without_ids() {
+        let parser = Parser::new().exclude_pages(false);
+        let site = parser
+            .parse_str(
+                r#"
+                <mediawiki>
+                    <page>
+                        <title>alpha</title>
+                        <revision><text>one</text></revision>
+                    </page>
+                    <page>
+                        <title>beta</title>
+                        <revision><text>two</text></revision>
+                    </page>
+                </mediawiki>
+                "#,
+            )
+            .expect("Could not parse mediawiki dump");
+
+        let mut writer = SqliteWriter::open_in_memory().expect("Could not create database");
+        writer.write_site(&site).expect("Could not write pages");
+        let connection = writer.finish().expect("Could not commit pages");
+
+        let title: String = connection
+            .query_row(
+                "SELECT title FROM pages JOIN revisions ON revisions.page_id = pages.id
+                 WHERE revisions.text = 'two'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(title, "beta");
+    }
+}
+
+#[cfg(test)]
+mod xml {
+    use wikidump::export::DumpWriter;
+    use wikidump::{Comment, Contributor, Parser, TextContent};
+
+    fn write_dump(site: &wikidump::Site) -> String {
+        let mut writer = DumpWriter::new(Vec::new());
+        writer.write_site(site).expect("Could not write pages");
+        String::from_utf8(writer.finish().expect("Could not finish dump")).unwrap()
+    }
+
+    #[test]
+    fn can_roundtrip_dump() {
+        let parser = Parser::new();
+        let site = parser
+            .parse_file("tests/simplewiki.xml")
+            .expect("Could not parse simplewiki dump");
+
+        let copy = parser
+            .parse_str(&write_dump(&site))
+            .expect("Could not parse written dump");
+
+        assert_eq!(copy.name, site.name);
+        assert_eq!(copy.dbname, site.dbname);
+        assert_eq!(copy.url, site.url);
+        assert_eq!(copy.namespaces.len(), site.namespaces.len());
+        assert_eq!(copy.pages.len(), site.pages.len());
+
+        for (page, copy) in site.pages.iter().zip(&copy.pages) {
+            assert_eq!(copy.title, page.title);
+            assert_eq!(copy.id, page.id);
+            assert_eq!(copy.revisions[0].raw, page.revisions[0].raw);
+            assert_eq!(copy.revisions[0].text, page.revisions[0].text);
+        }
+    }
+
+    #[test]
+    fn can_roundtrip_history_dump() {
+        let parser = Parser::new();
+        let site = parser
+            .parse_file("tests/simplewiki-history.xml")
+            .expect("Could not parse history dump");
+
+        let copy = parser
+            .parse_str(&write_dump(&site))
+            .expect("Could not parse written dump");
+        let revisions = &copy.pages[0].revisions;
+
+        assert_eq!(revisions.len(), 4);
+        assert_eq!(revisions[1].parent_id, Some(19031));
+        assert_eq!(
+            revisions[1].contributor,
+            Contributor::Anonymous {
+                ip: "198.51.100.7".to_string()
+            }
+        );
+        assert_eq!(revisions[2].contributor, Contributor::Deleted);
+        assert_eq!(revisions[2].comment, Comment::Deleted);
+        assert_eq!(revisions[2].text_content, TextContent::Deleted);
+        assert_eq!(revisions[3].comment, Comment::Empty);
+    }
+
+    #[test]
+    fn can_roundtrip_stub_dump_and_slots() {
+        let parser = Parser::new().process_text(false);
+
+        let stub = parser
+            .parse_file("tests/simplewiki-stub.xml")
+            .expect("Could not parse stub dump");
+        let copy = parser
+            .parse_str(&write_dump(&stub))
+            .expect("Could not parse written dump");
+        let revision = &copy.pages[0].revisions[0];
+        assert_eq!(revision.text_content, TextContent::NotIncluded);
+        assert_eq!(revision.text_bytes, Some(1523));
+        assert_eq!(revision.text_id, Some(1));
+
+        let parser = parser.exclude_pages(false);
+        let commons = parser
+            .parse_file("tests/commonswiki-0.11.xml")
+            .expect("Could not parse commonswiki dump");
+        let copy = parser
+            .parse_str(&write_dump(&commons))
+            .expect("Could not parse written dump");
+        let revision = &copy.pages[1].revisions[0];
+        assert_eq!(revision.origin, Some(790553870));
+        assert_eq!(revision.slots.len(), 1);
+        assert_eq!(revision.slots[0].role, "mediainfo");
+        assert_eq!(revision.slots[0].origin, Some(790554126));
+        assert_eq!(
+            revision.slots[0].text,
+            commons.pages[1].revisions[0].slots[0].text
+        );
+        assert_eq!(copy.pages[2].revisions[0].model, "Tabular.JsonConfig");
+
+        for (page, original) in copy.pages.iter().zip(&commons.pages) {
+            assert_eq!(
+                page.revisions[0].text_bytes,
+                original.revisions[0].text_bytes
+            );
+        }
+    }
+
+    #[test]
+    fn can_write_filtered_compressed_dump() {
+        let parser = Parser::new();
+        let site = parser
+            .parse_file("tests/simplewiki.xml")
+            .expect("Could not parse simplewiki dump");
+
+        let path = std::env::temp_dir().join("wikidump-filtered-test.xml.bz2");
+        let mut writer = DumpWriter::create(&path).expect("Could not create dump");
+        writer
+            .write_site_info(&site)
+            .expect("Could not write siteinfo");
+        for page in site.pages.iter().filter(|p| p.title.starts_with('A')) {
+            writer.write_page(page).expect("Could not write page");
+        }
+        // The dump is complete before the file is closed.
+        let file = writer.finish().expect("Could not finish dump");
+
+        let subset = parser
+            .parse_file(&path)
+            .expect("Could not parse written dump");
+        drop(file);
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(!subset.pages.is_empty());
+        assert!(subset.pages.iter().all(|p| p.title.starts_with('A')));
+        assert_eq!(
+            subset.pages.len(),
+            site.pages
+                .iter()
+                .filter(|p| p.title.starts_with('A'))
+                .count()
+        );
+    }
+}
+
+#[cfg(test)]
+mod extractor {
+    use bzip2::read::BzDecoder;
+    use std::fs;
+    use std::io::Read;
+    use std::path::PathBuf;
+    use wikidump::export::ExtractorWriter;
+    use wikidump::Parser;
+
+    fn output_directory(name: &str) -> PathBuf {
+        let directory = std::env::temp_dir().join(name);
+        let _ = fs::remove_dir_all(&directory);
+        directory
+    }
+
+    #[test]
+    fn can_write_documents() {
+        let parser = Parser::new();
+        let site = parser
+            .parse_file("tests/simplewiki.xml")
+            .expect("Could not parse simplewiki dump");
+
+        let directory = output_directory("wikidump-extractor-documents");
+        let mut writer = ExtractorWriter::create(&directory).expect("Could not create writer");
+        writer.write_site(&site).expect("Could not write pages");
+        writer.finish().expect("Could not finish writing");
+
+        let output = fs::read_to_string(directory.join("AA").join("wiki_00")).unwrap();
+        fs::remove_dir_all(&directory).unwrap();
+
+        let art = &site.pages[2];
+        let expected = format!(
+            "<doc id=\"6\" url=\"https://simple.wikipedia.org/wiki?curid=6\" title=\"Art\">\nArt\n\n{}\n</doc>\n",
+            art.revisions[0].text
+        );
+
+        assert!(output.contains(&expected));
+        assert_eq!(output.matches("<doc ").count(), site.pages.len());
+        assert_eq!(output.matches("</doc>\n").count(), site.pages.len());
+    }
+
+    #[test]
+    fn can_escape_documents() {
+        let dump = r#"
+            <mediawiki xmlns="http://www.mediawiki.org/xml/export-0.10/">
+                <page>
+                    <title>AT&amp;T "Labs"</title>
+                    <ns>0</ns>
+                    <id>7</id>
+                    <revision>
+                        <text>1 &lt; 2 &amp; 3 &gt; 2</text>
+                    </revision>
+                </page>
+            </mediawiki>
+        "#;
+        let site = Parser::new()
+            .parse_str(dump)
+            .expect("Could not parse mediawiki dump");
+
+        let directory = output_directory("wikidump-extractor-escaped");
+        let mut writer = ExtractorWriter::create(&directory).expect("Could not create writer");
+        writer.write_site(&site).expect("Could not write pages");
+        writer.finish().expect("Could not finish writing");
+
+        let output = fs::read_to_string(directory.join("AA").join("wiki_00")).unwrap();
+        fs::remove_dir_all(&directory).unwrap();
+
+        assert_eq!(
+            output,
+            "<doc id=\"7\" url=\"?curid=7\" title=\"AT&amp;T &quot;Labs&quot;\">\nAT&amp;T \"Labs\"\n\n1 &lt; 2 &amp; 3 &gt; 2\n</doc>\n"
+        );
+    }
+
+    #[test]
+    fn can_rotate_files_by_size() {
+        let parser = Parser::new();
+        let site = parser
+            .parse_file("tests/simplewiki.xml")
+            .expect("Could not parse simplewiki dump");
+
+        let directory = output_directory("wikidump-extractor-rotation");
+        let mut writer = ExtractorWriter::create(&directory)
+            .expect("Could not create writer")
+            .max_file_size(1);
+        writer.write_site(&site).expect("Could not write pages");
+        writer.finish().expect("Could not finish writing");
+
+        let mut names = fs::read_dir(directory.join("AA"))
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect::<Vec<_>>();
+        names.sort();
+        let first = fs::read_to_string(directory.join("AA").join("wiki_00")).unwrap();
+        fs::remove_dir_all(&directory).unwrap();
+
+        assert_eq!(names.len(), site.pages.len());
+        assert_eq!(names[0], "wiki_00");
+        assert_eq!(names[1], "wiki_01");
+        assert_eq!(first.matches("<doc ").count(), 1);
+    }
+
+    #[test]
+    fn can_compress_files() {
+        let parser = Parser::new();
+        let site = parser
+            .parse_file("tests/simplewiki.xml")
+            .expect("Could not parse simplewiki dump");
+
+        let directory = output_directory("wikidump-extractor-compressed");
+        let mut writer = ExtractorWriter::create(&directory)
+            .expect("Could not create writer")
+            .compress(true);
+        writer.write_site(&site).expect("Could not write pages");
+        writer.finish().expect("Could not finish writing");
+
+        let file = fs::File::open(directory.join("AA").join("wiki_00.bz2")).unwrap();
+        let mut output = String::new();
+        BzDecoder::new(file).read_to_string(&mut output).unwrap();
+        fs::remove_dir_all(&directory).unwrap();
+
+        assert_eq!(output.matches("<doc ").count(), site.pages.len());
+    }
+}
@@ -1,7 +1,16 @@
 #[cfg(test)]
 mod tests {
+    use parse_wiki_text::Node;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
     use wikidump::config;
-    use wikidump::Parser;
+    use wikidump::render::{render_plain_text, Output, PlainTextRenderer, Renderer};
+    use wikidump::{
+        dump_parts, BudgetAction, BudgetExceeded, CancellationToken, Checkpoint, Comment,
+        Contributor, DiffText, DiffUnit, Error, FailedPage, Page, PageContext, PageRevision,
+        PageVisitor, ParseWarning, Parser, Progress, RevisionDiff, RevisionSelection, Site,
+        TextContent, TitleFilter, Visit,
+    };
 
     #[test]
     fn can_create_parser() {
@@ -359,4 +368,2071 @@ This is paragraph 2.</text>
 
         assert_eq!(text, "This is paragraph 1.\nThis is paragraph 2.");
     }
+
+    // Split dump tests
+    const SIMPLEWIKI_PARTS: [&str; 2] = [
+        "tests/parts/simplewiki-pages-articles1.xml-p1p6",
+        "tests/parts/simplewiki-pages-articles2.xml-p8p12.bz2",
+    ];
+
+    #[test]
+    fn can_parse_split_dump() {
+        let parser = Parser::new().exclude_pages(false);
+
+        let site = parser
+            .parse_files(SIMPLEWIKI_PARTS)
+            .expect("Could not parse split simplewiki dump");
+
+        assert_eq!(site.name, "Wikipedia");
+        assert_eq!(site.dbname, "simplewiki");
+        assert_eq!(site.url, "https://simple.wikipedia.org/wiki/Main_Page");
+        assert_eq!(site.pages.len(), 7);
+
+        let whole = parser
+            .parse_file("tests/simplewiki.xml")
+            .expect("Could not parse simplewiki dump");
+
+        for (part_page, page) in site.pages.iter().zip(whole.pages.iter()) {
+            assert_eq!(part_page.title, page.title);
+            assert_eq!(part_page.revisions[0].text, page.revisions[0].text);
+        }
+    }
+
+    #[test]
+    fn can_stream_split_dump() {
+        let parser = Parser::new();
+
+        let stream = parser
+            .stream_files(SIMPLEWIKI_PARTS)
+            .expect("Could not open split simplewiki dump");
+
+        assert_eq!(stream.site().dbname, "simplewiki");
+        assert!(stream.site().pages.is_empty());
+
+        let titles = stream
+            .map(|page| page.expect("Could not read page").title)
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            titles,
+            vec![
+                "April",
+                "August",
+                "Art",
+                "A",
+                "Air",
+                "Autonomous communities of Spain"
+            ]
+        );
+    }
+
+    #[test]
+    fn can_move_streams_to_other_threads() {
+        let parser = Parser::new();
+        let pages = parser
+            .stream_files(SIMPLEWIKI_PARTS)
+            .expect("Could not open split simplewiki dump");
+        let revisions = parser
+            .stream_revisions(SIMPLEWIKI_PARTS)
+            .expect("Could not open split simplewiki dump");
+
+        std::thread::scope(|scope| {
+            let pages = scope.spawn(move || pages.filter(Result::is_ok).count());
+            let revisions = scope.spawn(move || revisions.filter(Result::is_ok).count());
+
+            assert_eq!(pages.join().unwrap(), 6);
+            assert_eq!(revisions.join().unwrap(), 6);
+        });
+    }
+
+    #[test]
+    fn will_reject_parts_from_different_sites() {
+        let parser = Parser::new();
+
+        let err = parser
+            .parse_files([SIMPLEWIKI_PARTS[0], "tests/enwiki-articles-partial.xml"])
+            .expect_err("Parts from different sites were accepted");
+
+        match err.downcast_ref::<Error>() {
+            Some(Error::SiteInfoMismatch {
+                field,
+                expected,
+                found,
+                ..
+            }) => {
+                assert_eq!(*field, "dbname");
+                assert_eq!(expected, "simplewiki");
+                assert_eq!(found, "enwiki");
+            }
+            _ => panic!("Unexpected error: {}", err),
+        }
+    }
+
+    #[test]
+    fn will_reject_empty_parts() {
+        let parser = Parser::new();
+        let parts: [&str; 0] = [];
+
+        let err = parser
+            .parse_files(parts)
+            .expect_err("No parts were accepted");
+
+        assert!(matches!(err.downcast_ref::<Error>(), Some(Error::NoParts)));
+    }
+
+    #[test]
+    fn can_find_dump_parts() {
+        let parts = dump_parts("tests/parts/simplewiki-pages-articles*")
+            .expect("Could not find split simplewiki dump");
+
+        assert_eq!(
+            parts,
+            SIMPLEWIKI_PARTS
+                .iter()
+                .map(std::path::PathBuf::from)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    // Export schema tests
+    #[test]
+    fn can_parse_schema_0_10_content_model() {
+        let parser = Parser::new();
+        let site = parser
+            .parse_file("tests/simplewiki.xml")
+            .expect("Could not parse simplewiki dump");
+
+        for page in site.pages {
+            for revision in page.revisions {
+                assert_eq!(revision.model, "wikitext");
+                assert_eq!(revision.format, "text/x-wiki");
+                assert!(revision.slots.is_empty());
+            }
+        }
+    }
+
+    #[test]
+    fn can_parse_schema_0_11_pages() {
+        let parser = Parser::new().exclude_pages(false);
+        let site = parser
+            .parse_file("tests/commonswiki-0.11.xml")
+            .expect("Could not parse commonswiki dump");
+
+        assert_eq!(site.name, "Wikimedia Commons");
+        assert_eq!(site.dbname, "commonswiki");
+        assert_eq!(site.pages.len(), 3);
+
+        for page in &site.pages {
+            let revision = &page.revisions[0];
+            assert_eq!(revision.text_bytes, Some(revision.raw.len() as u64));
+        }
+
+        let revision = &site.pages[0].revisions[0];
+
+        assert_eq!(revision.model, "wikitext");
+        assert!(revision.slots.is_empty());
+        assert!(revision
+            .text
+            .starts_with("Sunflowers (Helianthus) are plants with large yellow flowers."));
+    }
+
+    #[test]
+    fn can_parse_schema_0_11_slots() {
+        let parser = Parser::new().exclude_pages(false);
+        let site = parser
+            .parse_file("tests/commonswiki-0.11.xml")
+            .expect("Could not parse commonswiki dump");
+
+        let page = site
+            .pages
+            .iter()
+            .find(|&p| p.title == *"File:Sunflower in bloom.jpg")
+            .expect("Could not fetch example page");
+        let revision = &page.revisions[0];
+
+        assert_eq!(revision.origin, Some(790553870));
+        assert_eq!(revision.model, "wikitext");
+        assert_eq!(revision.format, "text/x-wiki");
+        assert!(revision.raw.starts_with("== {{int:filedesc}} =="));
+        assert_eq!(revision.slots.len(), 1);
+
+        let slot = revision.slot("mediainfo").expect("Could not get slot");
+
+        assert_eq!(slot.role, "mediainfo");
+        assert_eq!(slot.origin, Some(790554126));
+        assert_eq!(slot.model, "wikibase-mediainfo");
+        assert_eq!(slot.format, "application/json");
+        assert!(slot
+            .text
+            .starts_with(r#"{"type":"mediainfo","id":"M88341201""#));
+        assert!(revision.slot("main").is_none());
+    }
+
+    #[test]
+    fn will_not_process_other_content_models() {
+        let parser = Parser::new().exclude_pages(false);
+        let site = parser
+            .parse_file("tests/commonswiki-0.11.xml")
+            .expect("Could not parse commonswiki dump");
+
+        let page = site
+            .pages
+            .iter()
+            .find(|&p| p.title == *"Data:Sunflower heights.tab")
+            .expect("Could not fetch example page");
+        let revision = &page.revisions[0];
+
+        assert_eq!(revision.model, "Tabular.JsonConfig");
+        assert!(revision.text.starts_with(r#"{"license":"CC0-1.0""#));
+        assert_eq!(revision.text, revision.raw);
+    }
+
+    // Stub dump tests
+    #[test]
+    fn can_parse_page_and_revision_metadata() {
+        let parser = Parser::new().exclude_pages(false);
+        let site = parser
+            .parse_file("tests/simplewiki.xml")
+            .expect("Could not parse simplewiki dump");
+
+        let page = site
+            .pages
+            .iter()
+            .find(|&p| p.title == *"Wikipedia:Administrators")
+            .expect("Could not fetch example page");
+
+        assert_eq!(page.id, 11);
+        assert_eq!(page.namespace, 4);
+
+        let revision = &page.revisions[0];
+
+        assert_eq!(revision.id, 6635293);
+        assert_eq!(revision.parent_id, Some(6577720));
+        assert_eq!(revision.timestamp, "2019-08-14T10:53:35Z");
+        assert_eq!(revision.text_content, TextContent::Included);
+        assert!(!revision.sha1.is_empty());
+    }
+
+    #[test]
+    fn can_parse_stub_dump() {
+        let parser = Parser::new();
+        let site = parser
+            .parse_file("tests/simplewiki-stub.xml")
+            .expect("Could not parse simplewiki stub dump");
+
+        assert_eq!(site.pages.len(), 1);
+
+        let page = &site.pages[0];
+
+        assert_eq!(page.title, "April");
+        assert_eq!(page.id, 1);
+        assert_eq!(page.revisions.len(), 3);
+
+        let first = &page.revisions[0];
+
+        assert_eq!(first.id, 1);
+        assert_eq!(first.parent_id, None);
+        assert_eq!(first.timestamp, "2003-01-19T18:26:49Z");
+        assert_eq!(first.text_content, TextContent::NotIncluded);
+        assert_eq!(first.text_bytes, Some(1523));
+        assert_eq!(first.text_id, Some(1));
+        assert_eq!(first.sha1, "3jbvz5ydyoevnvh3tdvbkvt0fazkhid");
+        assert!(first.text.is_empty());
+        assert!(first.raw.is_empty());
+
+        let last = &page.revisions[2];
+
+        assert_eq!(last.id, 6516364);
+        assert_eq!(last.parent_id, Some(4123));
+        assert_eq!(last.text_content, TextContent::NotIncluded);
+        assert_eq!(last.text_bytes, Some(11893));
+        assert_eq!(last.text_id, Some(6587418));
+    }
+
+    #[test]
+    fn can_parse_deleted_text_in_stub_dump() {
+        let parser = Parser::new();
+        let site = parser
+            .parse_file("tests/simplewiki-stub.xml")
+            .expect("Could not parse simplewiki stub dump");
+
+        let revision = &site.pages[0].revisions[1];
+
+        assert_eq!(revision.id, 4123);
+        assert_eq!(revision.text_content, TextContent::Deleted);
+        assert_eq!(revision.text_bytes, None);
+        assert_eq!(revision.text_id, None);
+        assert!(revision.text.is_empty());
+    }
+
+    // History dump tests
+    #[test]
+    fn can_parse_contributors_and_comments() {
+        let parser = Parser::new();
+        let site = parser
+            .parse_file("tests/simplewiki-history.xml")
+            .expect("Could not parse simplewiki history dump");
+
+        let revisions = &site.pages[0].revisions;
+
+        assert_eq!(
+            revisions[0].contributor,
+            Contributor::User {
+                id: 3,
+                username: "Angela".to_string()
+            }
+        );
+        assert_eq!(revisions[0].comment, Comment::Text("new page".to_string()));
+        assert_eq!(
+            revisions[1].contributor,
+            Contributor::Anonymous {
+                ip: "198.51.100.7".to_string()
+            }
+        );
+        assert_eq!(revisions[1].contributor.name(), Some("198.51.100.7"));
+        assert_eq!(revisions[1].comment.text(), Some("/* Orbit */"));
+        assert_eq!(revisions[3].comment, Comment::Empty);
+    }
+
+    #[test]
+    fn can_parse_text_sizes() {
+        let parser = Parser::new().exclude_pages(false);
+        let site = parser
+            .parse_file("tests/simplewiki-history.xml")
+            .expect("Could not parse simplewiki history dump");
+
+        for revision in site.pages.iter().flat_map(|p| &p.revisions) {
+            match revision.text_content {
+                TextContent::Included => {
+                    assert_eq!(revision.text_bytes, Some(revision.raw.len() as u64))
+                }
+                _ => assert_eq!(revision.text_bytes, None),
+            }
+        }
+    }
+
+    #[test]
+    fn can_parse_deleted_revision_fields() {
+        let parser = Parser::new();
+        let site = parser
+            .parse_file("tests/simplewiki-history.xml")
+            .expect("Could not parse simplewiki history dump");
+
+        let revision = &site.pages[0].revisions[2];
+
+        assert_eq!(revision.id, 20611);
+        assert_eq!(revision.contributor, Contributor::Deleted);
+        assert_eq!(revision.contributor.name(), None);
+        assert_eq!(revision.comment, Comment::Deleted);
+        assert_eq!(revision.comment.text(), None);
+        assert_eq!(revision.text_content, TextContent::Deleted);
+        assert!(revision.text.is_empty());
+
+        let next = &site.pages[0].revisions[3];
+
+        assert_eq!(next.text_content, TextContent::Included);
+        assert_eq!(next.contributor.name(), Some("Angela"));
+    }
+
+    #[test]
+    fn can_parse_blanked_revisions() {
+        let parser = Parser::new().exclude_pages(false);
+        let site = parser
+            .parse_file("tests/simplewiki-history.xml")
+            .expect("Could not parse simplewiki history dump");
+
+        let revision = &site.pages[1].revisions[1];
+
+        assert_eq!(revision.id, 19046);
+        assert_eq!(revision.text_content, TextContent::Included);
+        assert_eq!(revision.text_bytes, Some(0));
+        assert_eq!(revision.text_id, None);
+        assert!(revision.raw.is_empty());
+        assert!(revision.text.is_empty());
+    }
+
+    // Visitor tests
+    /// Records every call, and returns the configured result for pages with
+    /// a given title.
+    #[derive(Default)]
+    struct RecordingVisitor {
+        events: Vec<String>,
+        skip_page: &'static str,
+        stop_page: &'static str,
+        skip_after_revision: u64,
+    }
+
+    impl PageVisitor for RecordingVisitor {
+        fn on_siteinfo(&mut self, site: &Site) -> Visit {
+            self.events.push(format!("site {}", site.dbname));
+            Visit::Continue
+        }
+
+        fn on_page_start(&mut self, page: &Page) -> Visit {
+            self.events.push(format!("start {}", page.title));
+
+            if page.title == self.stop_page {
+                Visit::Stop
+            } else if page.title == self.skip_page {
+                Visit::Skip
+            } else {
+                Visit::Continue
+            }
+        }
+
+        fn on_revision(&mut self, page: &Page, revision: &PageRevision) -> Visit {
+            self.events.push(format!(
+                "revision {} {} {}",
+                page.id,
+                revision.id,
+                page.revisions.len()
+            ));
+
+            if revision.id == self.skip_after_revision {
+                Visit::Skip
+            } else {
+                Visit::Continue
+            }
+        }
+
+        fn on_page_end(&mut self, page: &Page) -> Visit {
+            self.events
+                .push(format!("end {} {}", page.title, page.revisions.len()));
+            Visit::Continue
+        }
+
+        fn on_failed_page(&mut self, page: &FailedPage) -> Visit {
+            let title = page.title.as_deref().unwrap_or_default();
+            self.events.push(format!("failed {}", title));
+            Visit::Continue
+        }
+    }
+
+    #[test]
+    fn can_visit_pages_and_revisions() {
+        let parser = Parser::new().exclude_pages(false);
+        let mut visitor = RecordingVisitor::default();
+        parser
+            .visit_file("tests/simplewiki-history.xml", &mut visitor)
+            .expect("Could not visit history dump");
+
+        assert_eq!(
+            visitor.events,
+            [
+                "site simplewiki",
+                "start Moon",
+                "revision 2412 19031 0",
+                "revision 2412 20544 1",
+                "revision 2412 20611 2",
+                "revision 2412 20612 3",
+                "end Moon 4",
+                "start Talk:Moon",
+                "revision 2413 19045 0",
+                "revision 2413 19046 1",
+                "end Talk:Moon 2",
+            ]
+        );
+    }
+
+    #[test]
+    fn can_skip_and_stop_visiting() {
+        let parser = Parser::new().exclude_pages(false);
+
+        let mut visitor = RecordingVisitor {
+            skip_page: "Moon",
+            ..Default::default()
+        };
+        parser
+            .visit_file("tests/simplewiki-history.xml", &mut visitor)
+            .expect("Could not visit history dump");
+        assert_eq!(visitor.events[1..3], ["start Moon", "start Talk:Moon"]);
+        assert_eq!(visitor.events.last().unwrap(), "end Talk:Moon 2");
+
+        let mut visitor = RecordingVisitor {
+            skip_after_revision: 20544,
+            ..Default::default()
+        };
+        parser
+            .visit_file("tests/simplewiki-history.xml", &mut visitor)
+            .expect("Could not visit history dump");
+        assert_eq!(visitor.events[3], "revision 2412 20544 1");
+        assert_eq!(visitor.events[4], "start Talk:Moon");
+
+        let mut visitor = RecordingVisitor {
+            stop_page: "Talk:Moon",
+            ..Default::default()
+        };
+        parser
+            .visit_file("tests/simplewiki-history.xml", &mut visitor)
+            .expect("Could not visit history dump");
+        assert_eq!(visitor.events.last().unwrap(), "start Talk:Moon");
+    }
+
+    #[test]
+    fn visited_revisions_are_processed() {
+        struct Texts(Vec<String>);
+
+        impl PageVisitor for Texts {
+            fn on_revision(&mut self, _page: &Page, revision: &PageRevision) -> Visit {
+                self.0.push(revision.text.clone());
+                Visit::Continue
+            }
+        }
+
+        let parser = Parser::new();
+        let site = parser
+            .parse_file("tests/simplewiki.xml")
+            .expect("Could not parse simplewiki dump");
+
+        let mut texts = Texts(vec![]);
+        parser
+            .visit_file("tests/simplewiki.xml", &mut texts)
+            .expect("Could not visit simplewiki dump");
+
+        let expected = site
+            .pages
+            .iter()
+            .flat_map(|p| p.revisions.iter().map(|r| r.text.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(texts.0, expected);
+    }
+
+    // Renderer tests
+    const RENDERER_TEST: &str = r#"
+        <mediawiki xmlns="http://www.mediawiki.org/xml/export-0.10/">
+            <page>
+                <ns>0</ns>
+                <title>alpha</title>
+                <revision>
+                    <text>The [[Moon|moon]] has:
+* no air
+* [[Crater]]s</text>
+                </revision>
+            </page>
+        </mediawiki>
+    "#;
+
+    /// Keeps list bullets and marks the targets of links.
+    struct MarkupRenderer;
+
+    impl Renderer for MarkupRenderer {
+        fn render_node(&self, node: &Node, output: &mut Output) {
+            match node {
+                Node::UnorderedList { items, .. } => {
+                    for item in items {
+                        output.push_str("\n* ");
+                        self.render_nodes(&item.nodes, output);
+                    }
+                }
+                Node::Link { target, text, .. } => {
+                    output.push('[');
+                    self.render_nodes(text, output);
+                    output.push_str("](");
+                    output.push_str(target);
+                    output.push(')');
+                }
+                _ => render_plain_text(self, node, output),
+            }
+        }
+    }
+
+    #[test]
+    fn can_use_plain_text_renderer() {
+        let site = Parser::new()
+            .parse_str(RENDERER_TEST)
+            .expect("Could not parse mediawiki dump");
+        let plain = Parser::new()
+            .renderer(PlainTextRenderer)
+            .parse_str(RENDERER_TEST)
+            .expect("Could not parse mediawiki dump");
+
+        assert_eq!(
+            site.pages[0].revisions[0].text,
+            "The moon has:no airCraters"
+        );
+        assert_eq!(
+            plain.pages[0].revisions[0].text,
+            site.pages[0].revisions[0].text
+        );
+    }
+
+    #[test]
+    fn can_use_custom_renderer() {
+        let site = Parser::new()
+            .renderer(MarkupRenderer)
+            .parse_str(RENDERER_TEST)
+            .expect("Could not parse mediawiki dump");
+
+        assert_eq!(
+            site.pages[0].revisions[0].text,
+            "The [moon](Moon) has:\n* no air\n* [Craters](Crater)"
+        );
+    }
+
+    // Offset map tests
+    const OFFSETS_TEST: &str = r#"
+        <mediawiki xmlns="http://www.mediawiki.org/xml/export-0.10/">
+            <page>
+                <ns>0</ns>
+                <title>alpha</title>
+                <revision>
+                    <text>  The [[Moon|moon]] is bright &amp;ndash; at night.
+== Orbit ==
+It goes around the [[Earth]].  </text>
+                </revision>
+            </page>
+        </mediawiki>
+    "#;
+
+    fn raw_of<'r>(revision: &'r PageRevision, text: &str) -> &'r str {
+        let start = revision.text.find(text).expect("Could not find text");
+        let range = revision
+            .offsets
+            .as_ref()
+            .expect("Offsets are not mapped")
+            .to_raw(start..start + text.len())
+            .expect("Could not map text");
+
+        &revision.raw[range]
+    }
+
+    #[test]
+    fn can_map_offsets_to_raw_text() {
+        let parser = Parser::new().map_offsets(true);
+        let site = parser
+            .parse_str(OFFSETS_TEST)
+            .expect("Could not parse mediawiki dump");
+        let revision = &site.pages[0].revisions[0];
+
+        assert_eq!(
+            revision.text,
+            "The moon is bright \u{2013} at night.\nOrbit\nIt goes around the Earth."
+        );
+        assert_eq!(raw_of(revision, "The"), "The");
+        assert_eq!(raw_of(revision, "moon"), "moon");
+        assert_eq!(raw_of(revision, "The moon is"), "The [[Moon|moon]] is");
+        assert_eq!(raw_of(revision, "\u{2013}"), "&ndash;");
+        assert_eq!(raw_of(revision, "Orbit"), "Orbit");
+        assert_eq!(raw_of(revision, "Earth."), "Earth]].");
+    }
+
+    #[test]
+    fn can_map_offsets_after_removing_newlines() {
+        let parser = Parser::new().map_offsets(true).remove_newlines(true);
+        let site = parser
+            .parse_str(OFFSETS_TEST)
+            .expect("Could not parse mediawiki dump");
+        let revision = &site.pages[0].revisions[0];
+
+        assert!(revision
+            .text
+            .ends_with("at night.OrbitIt goes around the Earth."));
+        assert_eq!(raw_of(revision, "night.Orbit"), "night.\n== Orbit");
+        assert_eq!(raw_of(revision, "It goes"), "It goes");
+    }
+
+    #[test]
+    fn can_map_offsets_of_unprocessed_text() {
+        let parser = Parser::new().map_offsets(true).process_text(false);
+        let site = parser
+            .parse_str(OFFSETS_TEST)
+            .expect("Could not parse mediawiki dump");
+        let revision = &site.pages[0].revisions[0];
+
+        assert!(revision.text.starts_with("The [[Moon|moon]]"));
+        assert_eq!(raw_of(revision, "[[Earth]]"), "[[Earth]]");
+        assert_eq!(revision.offsets.as_ref().unwrap().to_raw(0..3), Some(2..5));
+    }
+
+    #[test]
+    fn mapping_offsets_does_not_change_text() {
+        for remove_newlines in [false, true] {
+            let site = Parser::new()
+                .remove_newlines(remove_newlines)
+                .parse_file("tests/simplewiki.xml")
+                .expect("Could not parse simplewiki dump");
+            let mapped = Parser::new()
+                .remove_newlines(remove_newlines)
+                .map_offsets(true)
+                .parse_file("tests/simplewiki.xml")
+                .expect("Could not parse simplewiki dump");
+
+            for (page, mapped_page) in site.pages.iter().zip(&mapped.pages) {
+                let revision = &page.revisions[0];
+                let mapped_revision = &mapped_page.revisions[0];
+                let offsets = mapped_revision.offsets.as_ref().unwrap();
+
+                assert!(revision.offsets.is_none());
+                assert_eq!(mapped_revision.text, revision.text);
+
+                // Every word maps to a valid range of the raw text
+                let text = &mapped_revision.text;
+                for word in text.split_whitespace() {
+                    let start = word.as_ptr() as usize - text.as_ptr() as usize;
+                    let raw = offsets.to_raw(start..start + word.len()).unwrap();
+
+                    assert!(mapped_revision.raw.get(raw).is_some());
+                }
+
+                assert_eq!(offsets.to_raw(0..text.len() + 1), None);
+            }
+        }
+    }
+
+    // Link span tests
+    const LINK_SPANS_TEST: &str = r#"
+        <mediawiki xmlns="http://www.mediawiki.org/xml/export-0.10/">
+            <page>
+                <ns>0</ns>
+                <title>alpha</title>
+                <revision>
+                    <text>
+The [[moon_landing#Apollo 11|first landing]] was on the [[Moon]].
+It left [[crater]]s and [[:Category:Flags|flags]].
+[[  Neil   Armstrong ]] walked first.</text>
+                </revision>
+            </page>
+        </mediawiki>
+    "#;
+
+    fn link_texts(revision: &PageRevision) -> Vec<(&str, &str, &str)> {
+        revision
+            .link_spans
+            .iter()
+            .map(|l| {
+                (
+                    &revision.text[l.start..l.end],
+                    l.target.as_str(),
+                    l.normalized_target.as_str(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn can_find_link_spans() {
+        let parser = Parser::new();
+        let site = parser
+            .parse_str(LINK_SPANS_TEST)
+            .expect("Could not parse mediawiki dump");
+        let revision = &site.pages[0].revisions[0];
+
+        assert_eq!(
+            link_texts(revision),
+            [
+                ("first landing", "moon_landing#Apollo 11", "Moon landing"),
+                ("Moon", "Moon", "Moon"),
+                ("craters", "crater", "Crater"),
+                ("flags", ":Category:Flags", "Category:Flags"),
+                ("Neil   Armstrong ", "Neil   Armstrong", "Neil Armstrong"),
+            ]
+        );
+    }
+
+    #[test]
+    fn can_find_link_spans_after_removing_newlines() {
+        let parser = Parser::new().remove_newlines(true);
+        let site = parser
+            .parse_str(LINK_SPANS_TEST)
+            .expect("Could not parse mediawiki dump");
+        let revision = &site.pages[0].revisions[0];
+        let texts = link_texts(revision)
+            .into_iter()
+            .map(|(text, _, _)| text)
+            .collect::<Vec<_>>();
+
+        assert!(revision.text.contains("Moon.It left"));
+        assert_eq!(
+            texts,
+            [
+                "first landing",
+                "Moon",
+                "craters",
+                "flags",
+                "Neil   Armstrong "
+            ]
+        );
+    }
+
+    #[test]
+    fn will_not_find_link_spans_in_unprocessed_text() {
+        let parser = Parser::new().process_text(false);
+        let site = parser
+            .parse_str(LINK_SPANS_TEST)
+            .expect("Could not parse mediawiki dump");
+
+        assert!(site.pages[0].revisions[0].link_spans.is_empty());
+    }
+
+    // Warning tests
+    const WARNINGS_TEST: &str = r#"
+        <mediawiki xmlns="http://www.mediawiki.org/xml/export-0.10/">
+            <page>
+                <ns>0</ns>
+                <title>alpha</title>
+                <revision>
+                    <text>A [[broken link and an &lt;b&gt;unclosed tag.</text>
+                </revision>
+            </page>
+            <page>
+                <ns>0</ns>
+                <title>beta</title>
+                <revision>
+                    <text>Nothing wrong here.</text>
+                </revision>
+            </page>
+            <page>
+                <ns>0</ns>
+                <title>gamma</title>
+                <revision>
+                    <text>Another [[broken link.</text>
+                </revision>
+            </page>
+        </mediawiki>
+    "#;
+
+    #[test]
+    fn can_keep_warnings() {
+        let parser = Parser::new();
+        let site = parser
+            .parse_str(WARNINGS_TEST)
+            .expect("Could not parse mediawiki dump");
+
+        let warnings = &site.pages[0].revisions[0].warnings;
+        let raw = &site.pages[0].revisions[0].raw;
+
+        assert!(warnings.contains(&ParseWarning {
+            start: 2,
+            end: 39,
+            kind: "InvalidLinkSyntax".to_string(),
+            message: "Invalid link syntax.".to_string(),
+        }));
+        assert!(warnings.iter().all(|w| raw.get(w.start..w.end).is_some()));
+        assert!(site.pages[1].revisions[0].warnings.is_empty());
+    }
+
+    #[test]
+    fn can_report_warnings() {
+        let parser = Parser::new();
+        let site = parser
+            .parse_str(WARNINGS_TEST)
+            .expect("Could not parse mediawiki dump");
+        let report = &site.report;
+
+        assert_eq!(report.processed_revisions, 3);
+        assert_eq!(report.warnings["InvalidLinkSyntax"], 2);
+        assert_eq!(
+            report.total_warnings(),
+            site.pages
+                .iter()
+                .map(|p| p.revisions[0].warnings.len() as u64)
+                .sum::<u64>()
+        );
+        assert_eq!(report.pages_with_warnings, ["alpha", "gamma"]);
+    }
+
+    #[test]
+    fn will_not_report_unprocessed_text() {
+        let parser = Parser::new().process_text(false);
+        let site = parser
+            .parse_str(WARNINGS_TEST)
+            .expect("Could not parse mediawiki dump");
+
+        assert!(site.pages[0].revisions[0].warnings.is_empty());
+        assert_eq!(site.report.processed_revisions, 0);
+        assert_eq!(site.report.total_warnings(), 0);
+    }
+
+    #[test]
+    fn can_report_warnings_of_split_dumps() {
+        let parser = Parser::new();
+        let site = parser
+            .parse_files(SIMPLEWIKI_PARTS)
+            .expect("Could not parse split dump");
+
+        let mut stream = parser
+            .stream_files(SIMPLEWIKI_PARTS)
+            .expect("Could not open split dump");
+        for page in &mut stream {
+            page.expect("Could not read page");
+        }
+
+        assert_eq!(site.report.processed_revisions, site.pages.len() as u64);
+        assert_eq!(stream.report(), &site.report);
+    }
+
+    // Processing budget tests
+    #[test]
+    fn can_keep_raw_text_over_size_limit() {
+        let parser = Parser::new().max_text_size(30);
+        let site = parser
+            .parse_str(WARNINGS_TEST)
+            .expect("Could not parse mediawiki dump");
+
+        let over = &site.pages[0].revisions[0];
+        assert_eq!(
+            over.budget_exceeded,
+            Some(BudgetExceeded::TextSize { bytes: 39 })
+        );
+        assert_eq!(over.text, over.raw);
+        assert!(over.warnings.is_empty());
+
+        let under = &site.pages[1].revisions[0];
+        assert_eq!(under.budget_exceeded, None);
+        assert_eq!(under.text, "Nothing wrong here.");
+
+        assert_eq!(site.report.processed_revisions, 2);
+        assert_eq!(site.report.over_budget.len(), 1);
+        assert_eq!(site.report.over_budget[0].title, "alpha");
+        assert_eq!(
+            site.report.over_budget[0].reason,
+            BudgetExceeded::TextSize { bytes: 39 }
+        );
+    }
+
+    #[test]
+    fn can_skip_text_over_size_limit() {
+        let parser = Parser::new()
+            .max_text_size(30)
+            .over_budget(BudgetAction::Skip);
+        let site = parser
+            .parse_str(WARNINGS_TEST)
+            .expect("Could not parse mediawiki dump");
+
+        let over = &site.pages[0].revisions[0];
+        assert!(over.budget_exceeded.is_some());
+        assert!(over.text.is_empty());
+        assert!(!over.raw.is_empty());
+        assert_eq!(site.pages[2].revisions[0].text, "Another [[broken link.");
+    }
+
+    #[test]
+    fn can_limit_processing_time() {
+        let parser = Parser::new()
+            .time_budget(Duration::ZERO)
+            .over_budget(BudgetAction::Skip);
+        let site = parser
+            .parse_str(WARNINGS_TEST)
+            .expect("Could not parse mediawiki dump");
+
+        assert_eq!(site.report.over_budget.len(), 3);
+        assert_eq!(site.report.processed_revisions, 0);
+
+        for page in &site.pages {
+            let revision = &page.revisions[0];
+
+            assert!(matches!(
+                revision.budget_exceeded,
+                Some(BudgetExceeded::Time { .. })
+            ));
+            assert!(revision.text.is_empty());
+        }
+    }
+
+    /// Takes far longer than any time budget in the tests, once for each
+    /// revision.
+    struct SlowRenderer;
+
+    impl Renderer for SlowRenderer {
+        fn render_node(&self, _node: &Node, _output: &mut Output) {}
+
+        fn render_nodes(&self, _nodes: &[Node], _output: &mut Output) {
+            std::thread::sleep(Duration::from_secs(1));
+        }
+    }
+
+    #[test]
+    fn will_stop_waiting_once_time_budget_is_used_up() {
+        let parser = Parser::new()
+            .renderer(SlowRenderer)
+            .time_budget(Duration::from_millis(50));
+        let site = parser
+            .parse_str(WARNINGS_TEST)
+            .expect("Could not parse mediawiki dump");
+
+        assert_eq!(site.report.over_budget.len(), 3);
+
+        let waited = site
+            .pages
+            .iter()
+            .map(|page| match page.revisions[0].budget_exceeded {
+                Some(BudgetExceeded::Time { elapsed }) => elapsed,
+                _ => panic!("Revision should be over the time budget"),
+            })
+            .collect::<Vec<_>>();
+        assert!(waited.iter().all(|&w| w >= Duration::from_millis(50)));
+        // At least the first revision is given up on rather than waited for.
+        assert!(waited.iter().any(|&w| w < Duration::from_secs(1)));
+
+        for page in &site.pages {
+            let revision = &page.revisions[0];
+            assert_eq!(revision.text, revision.raw.trim());
+        }
+
+        // Workers which were given up on are bounded by the threads of rayon.
+        let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+        let running = site.report.running_over_budget;
+        assert!((1..=threads.min(3)).contains(&running));
+    }
+
+    #[test]
+    fn can_reuse_workers_for_time_budget() {
+        let parse = |parser: Parser| {
+            let started = std::time::Instant::now();
+            let site = parser
+                .use_config(config::wikipedia::english())
+                .parse_file("benches/enwiki-10k.xml")
+                .expect("Could not parse wikipedia dump");
+            (site, started.elapsed())
+        };
+
+        let (expected, unlimited) = parse(Parser::new());
+        let (site, budgeted) = parse(Parser::new().time_budget(Duration::from_secs(60)));
+
+        assert!(site.report.over_budget.is_empty());
+        assert_eq!(site.report.running_over_budget, 0);
+        assert_eq!(
+            site.report.processed_revisions,
+            expected.report.processed_revisions
+        );
+
+        for (page, expected) in site.pages.iter().zip(&expected.pages) {
+            assert_eq!(page.revisions[0].text, expected.revisions[0].text);
+        }
+
+        // Handing each revision to a worker costs far less than parsing it.
+        assert!(budgeted < unlimited * 2 + Duration::from_millis(500));
+    }
+
+    #[test]
+    fn can_process_text_within_time_budget() {
+        let parser = Parser::new().time_budget(Duration::from_secs(60));
+        let site = parser
+            .parse_str(WARNINGS_TEST)
+            .expect("Could not parse mediawiki dump");
+        let expected = Parser::new()
+            .parse_str(WARNINGS_TEST)
+            .expect("Could not parse mediawiki dump");
+
+        assert!(site.report.over_budget.is_empty());
+
+        for (page, expected) in site.pages.iter().zip(&expected.pages) {
+            assert_eq!(page.revisions[0].text, expected.revisions[0].text);
+            assert_eq!(page.revisions[0].warnings, expected.revisions[0].warnings);
+        }
+    }
+
+    // Lenient parsing tests
+    const MALFORMED_TEST: &str = r#"
+        <mediawiki xmlns="http://www.mediawiki.org/xml/export-0.10/">
+            <page>
+                <ns>0</ns>
+                <title>alpha</title>
+                <revision>
+                    <text>An &bogus; entity.</text>
+                </revision>
+            </page>
+            <page>
+                <ns>0</ns>
+                <title>beta</title>
+                <revision>
+                    <text>Nothing wrong here.</text>
+                </revision>
+            </page>
+        </mediawiki>
+    "#;
+
+    const TRUNCATED_TEST: &str = r#"
+        <mediawiki xmlns="http://www.mediawiki.org/xml/export-0.10/">
+            <page>
+                <ns>0</ns>
+                <title>beta</title>
+                <revision>
+                    <text>Nothing wrong here.</text>
+                </revision>
+            </page>
+            <page>
+                <ns>0</ns>
+                <title>gamma</title>
+                <revision>
+                    <text>Cut off"#;
+
+    const MISMATCHED_TEST: &str = r#"
+        <mediawiki xmlns="http://www.mediawiki.org/xml/export-0.10/">
+            <page>
+                <ns>0</ns>
+                <title>alpha</title>
+                <revision>
+                    <text>Mismatched end tag.</txet>
+                </revision>
+            </page>
+            <page>
+                <ns>0</ns>
+                <title>beta</title>
+                <revision>
+                    <text>Nothing wrong here.</text>
+                </revision>
+            </page>
+        </mediawiki>
+    "#;
+
+    #[test]
+    fn will_return_error_for_malformed_xml() {
+        for dump in [MALFORMED_TEST, TRUNCATED_TEST] {
+            let err = Parser::new()
+                .parse_str(dump)
+                .expect_err("Malformed XML should not parse");
+
+            assert!(matches!(
+                err.downcast_ref::<Error>(),
+                Some(Error::Xml { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn can_skip_malformed_pages() {
+        let parser = Parser::new().lenient(true);
+        let site = parser
+            .parse_str(MALFORMED_TEST)
+            .expect("Could not parse mediawiki dump");
+
+        assert_eq!(site.pages.len(), 1);
+        assert_eq!(site.pages[0].title, "beta");
+        assert_eq!(site.pages[0].revisions[0].text, "Nothing wrong here.");
+
+        let failed = &site.report.failed_pages;
+        assert_eq!(failed.len(), 1);
+        assert_eq!(failed[0].title.as_deref(), Some("alpha"));
+        assert!(failed[0].position > 0);
+        assert!(!failed[0].error.is_empty());
+    }
+
+    #[test]
+    fn can_skip_truncated_page() {
+        let parser = Parser::new().lenient(true);
+        let site = parser
+            .parse_str(TRUNCATED_TEST)
+            .expect("Could not parse mediawiki dump");
+
+        assert_eq!(site.pages.len(), 1);
+        assert_eq!(site.pages[0].title, "beta");
+
+        let failed = &site.report.failed_pages;
+        assert_eq!(failed.len(), 1);
+        assert_eq!(failed[0].title.as_deref(), Some("gamma"));
+        assert_eq!(failed[0].position, TRUNCATED_TEST.len() as u64);
+    }
+
+    #[test]
+    fn can_check_end_names_in_strict_mode() {
+        let site = Parser::new()
+            .parse_str(MISMATCHED_TEST)
+            .expect("Could not parse mediawiki dump");
+        assert_eq!(site.pages.len(), 2);
+
+        let err = Parser::new()
+            .strict(true)
+            .parse_str(MISMATCHED_TEST)
+            .expect_err("Mismatched end tag should not parse");
+        assert!(matches!(
+            err.downcast_ref::<Error>(),
+            Some(Error::Xml { .. })
+        ));
+
+        let site = Parser::new()
+            .strict(true)
+            .lenient(true)
+            .parse_str(MISMATCHED_TEST)
+            .expect("Could not parse mediawiki dump");
+        assert_eq!(site.pages.len(), 1);
+        assert_eq!(site.pages[0].title, "beta");
+        assert_eq!(site.report.failed_pages[0].title.as_deref(), Some("alpha"));
+    }
+
+    #[test]
+    fn can_visit_failed_pages() {
+        let mut visitor = RecordingVisitor {
+            skip_after_revision: u64::MAX,
+            ..Default::default()
+        };
+        Parser::new()
+            .lenient(true)
+            .visit_str(MALFORMED_TEST, &mut visitor)
+            .expect("Could not visit mediawiki dump");
+
+        assert_eq!(
+            visitor.events,
+            [
+                "site ",
+                "start alpha",
+                "failed alpha",
+                "start beta",
+                "revision 0 0 0",
+                "end beta 1"
+            ]
+        );
+    }
+
+    // Progress tests
+    /// Returns a parser which records its progress after every page.
+    fn recording_progress() -> (Parser, Arc<Mutex<Vec<Progress>>>) {
+        let recorded = Arc::new(Mutex::new(vec![]));
+        let sink = Arc::clone(&recorded);
+        let parser = Parser::new()
+            .progress_interval(Duration::ZERO)
+            .on_progress(move |progress| sink.lock().unwrap().push(progress.clone()));
+
+        (parser, recorded)
+    }
+
+    #[test]
+    fn can_report_progress() {
+        let (parser, recorded) = recording_progress();
+        parser
+            .parse_file("tests/simplewiki.xml")
+            .expect("Could not parse mediawiki dump");
+
+        let recorded = recorded.lock().unwrap();
+        // One call per page, and one at the end of the file
+        assert_eq!(recorded.len(), 8);
+
+        for (before, after) in recorded.iter().zip(recorded.iter().skip(1)) {
+            assert!(before.bytes_read <= after.bytes_read);
+            assert!(before.pages_seen <= after.pages_seen);
+        }
+
+        let size = std::fs::metadata("tests/simplewiki.xml").unwrap().len();
+        let last = recorded.last().unwrap();
+        assert_eq!(last.pages_seen, 7);
+        assert_eq!(last.pages_kept, 6);
+        assert_eq!(last.pages_skipped, 1);
+        assert_eq!(last.bytes_read, size);
+        assert_eq!(last.file_bytes_read, size);
+        assert_eq!(last.file_size, Some(size));
+        assert_eq!(last.fraction(), Some(1.0));
+        assert_eq!(last.remaining(), Some(Duration::ZERO));
+    }
+
+    #[test]
+    fn can_report_progress_of_compressed_dump() {
+        let (parser, recorded) = recording_progress();
+        parser
+            .parse_file("tests/simplewiki.xml.bz2")
+            .expect("Could not parse mediawiki dump");
+
+        let size = std::fs::metadata("tests/simplewiki.xml.bz2").unwrap().len();
+        let recorded = recorded.lock().unwrap();
+        let last = recorded.last().unwrap();
+        assert_eq!(last.pages_kept, 6);
+        assert_eq!(last.file_bytes_read, size);
+        assert!(last.bytes_read > last.file_bytes_read);
+        assert_eq!(last.fraction(), Some(1.0));
+    }
+
+    #[test]
+    fn can_report_progress_of_split_dump() {
+        let (parser, recorded) = recording_progress();
+        let pages = parser
+            .stream_files(SIMPLEWIKI_PARTS)
+            .expect("Could not open split dump")
+            .count();
+
+        let size = SIMPLEWIKI_PARTS
+            .iter()
+            .map(|p| std::fs::metadata(p).unwrap().len())
+            .sum::<u64>();
+        let recorded = recorded.lock().unwrap();
+        let last = recorded.last().unwrap();
+        assert_eq!(last.pages_kept, pages as u64);
+        assert_eq!(last.file_bytes_read, size);
+        assert_eq!(last.file_size, Some(size));
+    }
+
+    #[test]
+    fn can_limit_progress_calls() {
+        let recorded = Arc::new(Mutex::new(vec![]));
+        let sink = Arc::clone(&recorded);
+        let parser = Parser::new()
+            .progress_interval(Duration::from_secs(3600))
+            .on_progress(move |progress| sink.lock().unwrap().push(progress.clone()));
+        parser
+            .parse_file("tests/simplewiki.xml")
+            .expect("Could not parse mediawiki dump");
+
+        // The first page and the end of the file
+        let recorded = recorded.lock().unwrap();
+        assert_eq!(recorded.len(), 2);
+        assert_eq!(recorded[0].pages_seen, 1);
+        assert_eq!(recorded[1].pages_seen, 7);
+    }
+
+    // Checkpoint tests
+    /// Reads every page of a dump, along with the checkpoint after it.
+    fn pages_with_checkpoints(parser: &Parser, parts: &[&str]) -> Vec<(u64, Checkpoint)> {
+        let mut stream = parser.stream_files(parts).expect("Could not open dump");
+        let mut pages = vec![];
+
+        while let Some(page) = stream.next() {
+            let page = page.expect("Could not read page");
+            pages.push((page.id, stream.checkpoint().unwrap().clone()));
+        }
+
+        pages
+    }
+
+    /// Checks that resuming from each checkpoint yields exactly the pages
+    /// which follow it.
+    fn assert_can_resume(parts: &[&str]) {
+        let pages = pages_with_checkpoints(&Parser::new(), parts);
+        assert!(!pages.is_empty());
+
+        for (index, (id, checkpoint)) in pages.iter().enumerate() {
+            assert_eq!(checkpoint.page_id, *id);
+            assert!(checkpoint.stream_position <= checkpoint.position);
+
+            // Resuming must not upset checking end tags either
+            let parser = Parser::new().strict(true).resume_from(checkpoint.clone());
+            let rest = parser
+                .stream_files(parts)
+                .expect("Could not resume dump")
+                .map(|page| page.expect("Could not read page").id)
+                .collect::<Vec<_>>();
+            let expected = pages[index + 1..]
+                .iter()
+                .map(|(id, _)| *id)
+                .collect::<Vec<_>>();
+
+            assert_eq!(rest, expected);
+        }
+    }
+
+    #[test]
+    fn can_resume_split_dump_from_checkpoint() {
+        assert_can_resume(&SIMPLEWIKI_PARTS);
+
+        let pages = pages_with_checkpoints(&Parser::new(), &SIMPLEWIKI_PARTS);
+        let (_, first) = &pages[0];
+        assert_eq!(first.part, 0);
+        assert_eq!(first.stream_offset, first.position);
+        assert_eq!(pages.last().unwrap().1.part, 1);
+    }
+
+    #[test]
+    fn can_resume_multistream_dump_from_checkpoint() {
+        use bzip2::write::BzEncoder;
+        use bzip2::Compression;
+        use std::io::Write;
+
+        // Compress the header and each page as a stream of its own, like
+        // the multistream dumps of Wikipedia.
+        let xml = std::fs::read_to_string("tests/simplewiki.xml").unwrap();
+        let mut chunks = xml.split_inclusive("</page>").collect::<Vec<_>>();
+        let first_page = chunks[0].find("<page>").unwrap();
+        let (header, page) = chunks[0].split_at(first_page);
+        chunks.splice(0..1, [header, page]);
+
+        let mut compressed = vec![];
+        for chunk in chunks {
+            let mut encoder = BzEncoder::new(vec![], Compression::fast());
+            encoder.write_all(chunk.as_bytes()).unwrap();
+            compressed.extend(encoder.finish().unwrap());
+        }
+
+        let path = std::env::temp_dir().join("wikidump-multistream-test.xml.bz2");
+        std::fs::write(&path, compressed).unwrap();
+        let path = path.to_str().unwrap();
+
+        assert_can_resume(&[path]);
+
+        let pages = pages_with_checkpoints(&Parser::new(), &[path]);
+        let offsets = pages
+            .iter()
+            .map(|(_, c)| c.stream_offset)
+            .collect::<Vec<_>>();
+        assert!(offsets[0] > 0);
+        assert!(offsets.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[test]
+    fn will_report_progress_after_checkpoint() {
+        let pages = pages_with_checkpoints(&Parser::new(), &SIMPLEWIKI_PARTS);
+        let (_, checkpoint) = pages[pages.len() - 2].clone();
+
+        let (parser, recorded) = recording_progress();
+        let parser = parser.resume_from(checkpoint);
+        let rest = parser.stream_files(SIMPLEWIKI_PARTS).unwrap().count();
+        assert_eq!(rest, 1);
+
+        let recorded = recorded.lock().unwrap();
+        let last = recorded.last().unwrap();
+        assert_eq!(last.pages_kept, 1);
+        assert!(last.file_bytes_skipped > 0);
+        assert_eq!(last.fraction(), Some(1.0));
+    }
+
+    #[test]
+    fn will_not_resume_from_invalid_checkpoint() {
+        let checkpoint = Checkpoint {
+            part: 2,
+            page_id: 0,
+            position: 0,
+            stream_offset: 0,
+            stream_position: 0,
+        };
+        let parser = Parser::new().resume_from(checkpoint);
+        let err = parser
+            .stream_files(SIMPLEWIKI_PARTS)
+            .err()
+            .expect("Checkpoint should not fit the dump");
+
+        assert!(matches!(
+            err.downcast_ref::<Error>(),
+            Some(Error::InvalidCheckpoint)
+        ));
+    }
+
+    // Cancellation and limit tests
+    #[test]
+    fn can_limit_number_of_pages() {
+        let site = Parser::new()
+            .max_pages(3)
+            .parse_file("tests/simplewiki.xml")
+            .expect("Could not parse mediawiki dump");
+        let titles = site
+            .pages
+            .iter()
+            .map(|p| p.title.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(titles, ["April", "August", "Art"]);
+
+        let all = Parser::new()
+            .stream_files(SIMPLEWIKI_PARTS)
+            .expect("Could not open split dump")
+            .count();
+        let limited = Parser::new()
+            .max_pages(all as u64 - 1)
+            .stream_files(SIMPLEWIKI_PARTS)
+            .expect("Could not open split dump")
+            .count();
+        assert_eq!(limited, all - 1);
+    }
+
+    #[test]
+    fn can_limit_number_of_bytes() {
+        let xml = std::fs::read_to_string("tests/simplewiki.xml").unwrap();
+        let first_page_end = (xml.find("</page>").unwrap() + "</page>".len()) as u64;
+
+        // The page which is being read when the limit is reached is finished
+        let site = Parser::new()
+            .max_bytes(first_page_end - 1)
+            .parse_file("tests/simplewiki.xml")
+            .expect("Could not parse mediawiki dump");
+        assert_eq!(site.pages.len(), 1);
+        assert_eq!(site.pages[0].title, "April");
+        assert!(!site.pages[0].revisions[0].text.is_empty());
+
+        let site = Parser::new()
+            .max_bytes(first_page_end + 1)
+            .parse_file("tests/simplewiki.xml")
+            .expect("Could not parse mediawiki dump");
+        assert_eq!(site.pages.len(), 2);
+    }
+
+    #[test]
+    fn can_cancel_parse() {
+        let token = CancellationToken::new();
+        let parser = Parser::new().cancellation_token(token.clone());
+        assert!(parser.parse_file("tests/simplewiki.xml").is_ok());
+
+        token.cancel();
+        for result in [
+            parser.parse_file("tests/simplewiki.xml"),
+            parser.parse_files(SIMPLEWIKI_PARTS),
+        ] {
+            let err = result.expect_err("Cancelled parse should fail");
+            assert!(matches!(
+                err.downcast_ref::<Error>(),
+                Some(Error::Cancelled)
+            ));
+        }
+    }
+
+    #[test]
+    fn can_cancel_while_visiting() {
+        struct CancelAfter(CancellationToken, Vec<String>);
+
+        impl PageVisitor for CancelAfter {
+            fn on_page_end(&mut self, page: &Page) -> Visit {
+                self.1.push(page.title.clone());
+
+                if self.1.len() == 2 {
+                    self.0.cancel();
+                }
+
+                Visit::Continue
+            }
+        }
+
+        let token = CancellationToken::new();
+        let mut visitor = CancelAfter(token.clone(), vec![]);
+        let err = Parser::new()
+            .lenient(true)
+            .cancellation_token(token)
+            .visit_file("tests/simplewiki.xml", &mut visitor)
+            .expect_err("Cancelled parse should fail");
+
+        assert!(matches!(
+            err.downcast_ref::<Error>(),
+            Some(Error::Cancelled)
+        ));
+        assert_eq!(visitor.1, ["April", "August"]);
+    }
+
+    #[test]
+    fn can_cancel_stream() {
+        let token = CancellationToken::new();
+        let parser = Parser::new().cancellation_token(token.clone());
+        let mut stream = parser
+            .stream_files(SIMPLEWIKI_PARTS)
+            .expect("Could not open split dump");
+
+        assert!(stream.next().unwrap().is_ok());
+        token.cancel();
+
+        let err = stream
+            .next()
+            .unwrap()
+            .expect_err("Cancelled stream should fail");
+        assert!(matches!(
+            err.downcast_ref::<Error>(),
+            Some(Error::Cancelled)
+        ));
+        assert!(stream.next().is_none());
+    }
+
+    // Title filter tests
+    fn titles_of(parser: Parser) -> Vec<String> {
+        let site = parser
+            .parse_file("tests/simplewiki.xml")
+            .expect("Could not parse mediawiki dump");
+
+        site.pages.into_iter().map(|p| p.title).collect()
+    }
+
+    #[test]
+    fn can_filter_exact_titles() {
+        let filter = TitleFilter::exact(["Art", "Autonomous_communities_of_Spain", "Not a page"]);
+        assert!(filter.matches("Autonomous communities of Spain"));
+        assert!(!filter.matches("Autonomous_communities_of_Spain"));
+
+        let titles = titles_of(Parser::new().filter_titles(filter));
+        assert_eq!(titles, ["Art", "Autonomous communities of Spain"]);
+    }
+
+    #[test]
+    fn can_filter_titles_from_file() {
+        let path = std::env::temp_dir().join("wikidump-title-filter-test.txt");
+        std::fs::write(&path, "August\n\n  Art  \n").unwrap();
+
+        let filter = TitleFilter::from_file(&path).expect("Could not read titles");
+        let titles = titles_of(Parser::new().filter_titles(filter));
+        assert_eq!(titles, ["August", "Art"]);
+    }
+
+    #[test]
+    fn can_filter_title_prefixes() {
+        let titles = titles_of(Parser::new().filter_titles(TitleFilter::prefix("A")));
+        assert_eq!(
+            titles,
+            [
+                "April",
+                "August",
+                "Art",
+                "A",
+                "Air",
+                "Autonomous communities of Spain"
+            ]
+        );
+
+        let titles = titles_of(Parser::new().filter_titles(TitleFilter::prefix("Ap")));
+        assert_eq!(titles, ["April"]);
+    }
+
+    #[test]
+    #[cfg(feature = "regex")]
+    fn can_filter_titles_with_regex() {
+        let filter = TitleFilter::regex("^A[a-z]*t$").expect("Invalid regex");
+        let titles = titles_of(Parser::new().filter_titles(filter));
+        assert_eq!(titles, ["August", "Art"]);
+
+        assert!(TitleFilter::regex("(").is_err());
+    }
+
+    #[test]
+    fn can_filter_titles_with_closure() {
+        let filter = TitleFilter::custom(|title| title.len() == 3);
+        let titles = titles_of(Parser::new().filter_titles(filter));
+        assert_eq!(titles, ["Art", "Air"]);
+    }
+
+    #[test]
+    fn will_not_visit_filtered_pages() {
+        let mut visitor = RecordingVisitor::default();
+        Parser::new()
+            .filter_titles(TitleFilter::exact(["Art"]))
+            .visit_file("tests/simplewiki.xml", &mut visitor)
+            .expect("Could not visit mediawiki dump");
+
+        let started = visitor
+            .events
+            .iter()
+            .filter(|e| e.starts_with("start"))
+            .collect::<Vec<_>>();
+        assert_eq!(started, ["start Art"]);
+    }
+
+    // Skipped page tests
+    const SKIPPED_TEST: &str = r#"
+        <mediawiki xmlns="http://www.mediawiki.org/xml/export-0.10/">
+            <page>
+                <title>Talk:&bogus;</title>
+                <ns>1</ns>
+                <revision>
+                    <text>An &bogus; entity.</text>
+                </revision>
+            </page>
+            <page>
+                <title>Q &amp; A</title>
+                <ns>0</ns>
+                <revision>
+                    <text>Nothing wrong here.</text>
+                </revision>
+            </page>
+        </mediawiki>
+    "#;
+
+    #[test]
+    fn will_not_decode_excluded_pages() {
+        let site = Parser::new()
+            .parse_str(SKIPPED_TEST)
+            .expect("Could not parse mediawiki dump");
+
+        assert_eq!(site.pages.len(), 1);
+        assert_eq!(site.pages[0].title, "Q & A");
+        assert_eq!(site.pages[0].revisions[0].text, "Nothing wrong here.");
+        assert!(site.report.failed_pages.is_empty());
+    }
+
+    #[test]
+    fn will_decode_pages_which_are_not_excluded() {
+        let err = Parser::new()
+            .exclude_pages(false)
+            .parse_str(SKIPPED_TEST)
+            .expect_err("Malformed XML should not parse");
+
+        assert!(matches!(
+            err.downcast_ref::<Error>(),
+            Some(Error::Xml { .. })
+        ));
+    }
+
+    #[test]
+    fn will_not_decode_pages_skipped_by_visitor() {
+        let mut visitor = RecordingVisitor {
+            skip_page: "alpha",
+            skip_after_revision: u64::MAX,
+            ..RecordingVisitor::default()
+        };
+        Parser::new()
+            .visit_str(MALFORMED_TEST, &mut visitor)
+            .expect("Could not visit mediawiki dump");
+
+        assert_eq!(visitor.events[1], "start alpha");
+        assert_eq!(visitor.events[2], "start beta");
+        assert_eq!(visitor.events.last().unwrap(), "end beta 1");
+    }
+
+    #[test]
+    fn will_count_skipped_pages() {
+        let (parser, calls) = recording_progress();
+        parser
+            .parse_str(SKIPPED_TEST)
+            .expect("Could not parse mediawiki dump");
+
+        let last = calls.lock().unwrap().last().cloned().unwrap();
+        assert_eq!(last.pages_seen, 2);
+        assert_eq!(last.pages_kept, 1);
+        assert_eq!(last.pages_skipped, 1);
+    }
+
+    // Revision selection tests
+    fn revision_ids(selection: RevisionSelection) -> Vec<Vec<u64>> {
+        let parser = Parser::new()
+            .exclude_pages(false)
+            .select_revisions(selection);
+        let site = parser
+            .parse_file("tests/simplewiki-history.xml")
+            .expect("Could not parse history dump");
+
+        site.pages
+            .iter()
+            .map(|p| p.revisions.iter().map(|r| r.id).collect())
+            .collect()
+    }
+
+    #[test]
+    fn can_select_all_revisions() {
+        let ids = revision_ids(RevisionSelection::All);
+        assert_eq!(ids, [vec![19031, 20544, 20611, 20612], vec![19045, 19046]]);
+    }
+
+    #[test]
+    fn can_select_latest_revision() {
+        let ids = revision_ids(RevisionSelection::Latest);
+        assert_eq!(ids, [vec![20612], vec![19046]]);
+    }
+
+    #[test]
+    fn can_select_first_revision() {
+        let ids = revision_ids(RevisionSelection::First);
+        assert_eq!(ids, [vec![19031], vec![19045]]);
+    }
+
+    #[test]
+    fn can_select_every_nth_revision() {
+        let ids = revision_ids(RevisionSelection::EveryNth(2));
+        assert_eq!(ids, [vec![19031, 20611], vec![19045]]);
+
+        let ids = revision_ids(RevisionSelection::EveryNth(3));
+        assert_eq!(ids, [vec![19031, 20612], vec![19045]]);
+    }
+
+    #[test]
+    fn can_select_revisions_between_timestamps() {
+        let selection = RevisionSelection::between(Some("2004-03-02"), Some("2004-03-03T01:20"));
+        assert_eq!(revision_ids(selection), [vec![20544, 20611], vec![]]);
+
+        let selection = RevisionSelection::between(None, Some("2004-03"));
+        assert_eq!(revision_ids(selection), [vec![19031], vec![19045, 19046]]);
+    }
+
+    #[test]
+    fn can_select_revisions_by_contributor() {
+        let selection = RevisionSelection::contributors(["Angela"]);
+        assert_eq!(revision_ids(selection), [vec![19031, 20612], vec![19045]]);
+
+        let selection = RevisionSelection::contributors(["198.51.100.7"]);
+        assert_eq!(revision_ids(selection), [vec![20544], vec![19046]]);
+    }
+
+    #[test]
+    fn will_not_read_text_of_unselected_revisions() {
+        let dump = r#"
+            <mediawiki xmlns="http://www.mediawiki.org/xml/export-0.10/">
+                <page>
+                    <title>alpha</title>
+                    <ns>0</ns>
+                    <revision>
+                        <text>First.</text>
+                    </revision>
+                    <revision>
+                        <text>An &bogus; entity.</text>
+                    </revision>
+                    <revision>
+                        <text>Third.</text>
+                    </revision>
+                </page>
+            </mediawiki>
+        "#;
+        let site = Parser::new()
+            .select_revisions(RevisionSelection::EveryNth(2))
+            .parse_str(dump)
+            .expect("Could not parse mediawiki dump");
+
+        let texts = site.pages[0]
+            .revisions
+            .iter()
+            .map(|r| r.text.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(texts, ["First.", "Third."]);
+    }
+
+    #[test]
+    fn will_visit_selected_revisions() {
+        let parser = Parser::new()
+            .exclude_pages(false)
+            .select_revisions(RevisionSelection::Latest);
+        let mut visitor = RecordingVisitor::default();
+        parser
+            .visit_file("tests/simplewiki-history.xml", &mut visitor)
+            .expect("Could not visit history dump");
+
+        assert_eq!(
+            visitor.events,
+            [
+                "site simplewiki",
+                "start Moon",
+                "revision 2412 20612 0",
+                "end Moon 1",
+                "start Talk:Moon",
+                "revision 2413 19046 0",
+                "end Talk:Moon 1",
+            ]
+        );
+
+        let parser = Parser::new()
+            .exclude_pages(false)
+            .select_revisions(RevisionSelection::contributors(["Angela"]));
+        let mut visitor = RecordingVisitor::default();
+        parser
+            .visit_file("tests/simplewiki-history.xml", &mut visitor)
+            .expect("Could not visit history dump");
+
+        assert_eq!(
+            visitor.events[2..5],
+            [
+                "revision 2412 19031 0",
+                "revision 2412 20612 1",
+                "end Moon 2"
+            ]
+        );
+    }
+
+    // Revision stream tests
+    fn streamed_revisions(parser: Parser, parts: &[&str]) -> Vec<(PageContext, PageRevision)> {
+        parser
+            .stream_revisions(parts)
+            .expect("Could not open mediawiki dump")
+            .collect::<Result<Vec<_>, _>>()
+            .expect("Could not stream mediawiki dump")
+    }
+
+    fn streamed_ids(parser: Parser, parts: &[&str]) -> Vec<(String, u64)> {
+        streamed_revisions(parser, parts)
+            .into_iter()
+            .map(|(page, revision)| (page.title, revision.id))
+            .collect()
+    }
+
+    #[test]
+    fn can_stream_revisions() {
+        let parser = Parser::new().exclude_pages(false);
+        let revisions = streamed_revisions(parser, &["tests/simplewiki-history.xml"]);
+
+        let site = Parser::new()
+            .exclude_pages(false)
+            .parse_file("tests/simplewiki-history.xml")
+            .expect("Could not parse history dump");
+        let expected = site
+            .pages
+            .iter()
+            .flat_map(|p| p.revisions.iter().map(move |r| (p, r)))
+            .collect::<Vec<_>>();
+
+        assert_eq!(revisions.len(), expected.len());
+
+        for ((page, revision), (expected_page, expected_revision)) in revisions.iter().zip(expected)
+        {
+            assert_eq!(page, &PageContext::from(expected_page));
+            assert_eq!(revision.id, expected_revision.id);
+            assert_eq!(revision.text, expected_revision.text);
+        }
+
+        assert_eq!(revisions[4].0.namespace, 1);
+    }
+
+    #[test]
+    fn can_stream_selected_revisions() {
+        let parser = Parser::new()
+            .exclude_pages(false)
+            .select_revisions(RevisionSelection::Latest);
+        let ids = streamed_ids(parser, &["tests/simplewiki-history.xml"]);
+        assert_eq!(
+            ids,
+            [
+                ("Moon".to_string(), 20612),
+                ("Talk:Moon".to_string(), 19046)
+            ]
+        );
+
+        let parser = Parser::new().select_revisions(RevisionSelection::EveryNth(2));
+        let ids = streamed_ids(parser, &["tests/simplewiki-history.xml"]);
+        assert_eq!(
+            ids,
+            [("Moon".to_string(), 19031), ("Moon".to_string(), 20611)]
+        );
+    }
+
+    #[test]
+    fn can_stream_revisions_of_split_dump() {
+        let parts = [
+            "tests/parts/simplewiki-pages-articles1.xml-p1p6",
+            "tests/parts/simplewiki-pages-articles2.xml-p8p12.bz2",
+        ];
+        let ids = streamed_ids(Parser::new(), &parts);
+
+        let site = Parser::new()
+            .parse_files(parts)
+            .expect("Could not parse split dump");
+        let expected = site
+            .pages
+            .iter()
+            .flat_map(|p| p.revisions.iter().map(move |r| (p.title.clone(), r.id)))
+            .collect::<Vec<_>>();
+        assert_eq!(ids, expected);
+
+        let ids = streamed_ids(Parser::new().max_pages(2), &parts);
+        assert_eq!(ids, expected[..2]);
+    }
+
+    #[test]
+    fn can_skip_malformed_pages_while_streaming_revisions() {
+        let path = std::env::temp_dir().join("wikidump-revision-stream-test.xml");
+        std::fs::write(&path, MALFORMED_TEST).unwrap();
+        let path = path.to_str().unwrap();
+
+        let parser = Parser::new().lenient(true);
+        let mut stream = parser
+            .stream_revisions([path])
+            .expect("Could not open mediawiki dump");
+        let (page, revision) = stream.next().unwrap().expect("Could not read revision");
+        assert_eq!(page.title, "beta");
+        assert_eq!(revision.text, "Nothing wrong here.");
+        assert!(stream.next().is_none());
+
+        let failed = &stream.report().failed_pages;
+        assert_eq!(failed.len(), 1);
+        assert_eq!(failed[0].title.as_deref(), Some("alpha"));
+
+        let parser = Parser::new();
+        let mut stream = parser
+            .stream_revisions([path])
+            .expect("Could not open mediawiki dump");
+        assert!(stream.next().unwrap().is_err());
+        assert!(stream.next().is_none());
+    }
+
+    // Diff tests
+    fn moon() -> Page {
+        let site = Parser::new()
+            .exclude_pages(false)
+            .parse_file("tests/simplewiki-history.xml")
+            .expect("Could not parse history dump");
+
+        site.pages[0].clone()
+    }
+
+    #[test]
+    fn can_find_parent_revisions() {
+        let page = moon();
+        let parents = page
+            .revisions
+            .iter()
+            .map(|r| page.parent_of(r).map(|p| p.id))
+            .collect::<Vec<_>>();
+
+        assert_eq!(parents, [None, Some(19031), Some(20544), Some(20611)]);
+    }
+
+    #[test]
+    fn can_diff_revisions_by_word() {
+        let page = moon();
+        let diffs = page.diffs(DiffText::Raw, DiffUnit::Words);
+
+        // The last revision is left out, since the text of its parent has
+        // been deleted.
+        let ids = diffs
+            .iter()
+            .map(|d| (d.revision_id, d.parent_id))
+            .collect::<Vec<_>>();
+        assert_eq!(ids, [(19031, None), (20544, Some(19031))]);
+
+        assert_eq!(diffs[0].added.len(), 1);
+        assert_eq!(diffs[0].added[0].text, page.revisions[0].raw);
+        assert!(diffs[0].removed.is_empty());
+
+        let old = &page.revisions[0].raw;
+        let added = &diffs[1].added;
+        assert_eq!(added.len(), 1);
+        assert_eq!(added[0].start, old.len());
+        assert_eq!(
+            added[0].text,
+            "\n\n== Orbit ==\nThe Moon goes around the Earth once every 27 [[day]]s."
+        );
+        assert!(diffs[1].removed.is_empty());
+    }
+
+    #[test]
+    fn can_diff_blanked_revisions() {
+        let site = Parser::new()
+            .exclude_pages(false)
+            .parse_file("tests/simplewiki-history.xml")
+            .expect("Could not parse history dump");
+        let page = &site.pages[1];
+        let diffs = page.diffs(DiffText::Raw, DiffUnit::Lines);
+
+        assert_eq!(diffs.len(), 2);
+        assert_eq!(diffs[1].revision_id, 19046);
+        assert_eq!(diffs[1].parent_id, Some(19045));
+        assert!(diffs[1].added.is_empty());
+        assert_eq!(diffs[1].removed.len(), 1);
+        assert_eq!(diffs[1].removed[0].text, page.revisions[0].raw);
+    }
+
+    #[test]
+    fn can_diff_revisions_by_line() {
+        let page = moon();
+        let diff = RevisionDiff::between(
+            Some(&page.revisions[1]),
+            &page.revisions[3],
+            DiffText::Raw,
+            DiffUnit::Lines,
+        );
+
+        assert_eq!(diff.revision_id, 20612);
+        assert_eq!(diff.parent_id, Some(20544));
+
+        let removed = diff
+            .removed
+            .iter()
+            .map(|r| r.text.as_str())
+            .collect::<Vec<_>>();
+        let added = diff
+            .added
+            .iter()
+            .map(|r| r.text.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            removed,
+            ["The Moon goes around the Earth once every 27 [[day]]s."]
+        );
+        assert_eq!(
+            added,
+            ["The Moon goes around the Earth once every 27 [[day]]s.\nIt always shows the same side to the Earth."]
+        );
+
+        let raw = &page.revisions[3].raw;
+        assert_eq!(&raw[diff.added[0].start..diff.added[0].end], added[0]);
+    }
+
+    #[test]
+    fn can_diff_processed_text() {
+        let page = moon();
+        let diff = RevisionDiff::between(
+            Some(&page.revisions[1]),
+            &page.revisions[3],
+            DiffText::Processed,
+            DiffUnit::Words,
+        );
+
+        let added = diff
+            .added
+            .iter()
+            .map(|r| r.text.as_str())
+            .collect::<String>();
+        assert_eq!(added, "\nIt always shows the same side to the Earth.");
+        assert!(diff.removed.is_empty());
+        assert!(!page.revisions[3].text.contains("[[day]]"));
+    }
 }
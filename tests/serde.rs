@@ -0,0 +1,61 @@
+#![cfg(feature = "serde")]
+
+#[cfg(test)]
+mod tests {
+    use wikidump::{Comment, Contributor, Parser, Site, TextContent};
+
+    #[test]
+    fn can_serialize_and_deserialize_site() {
+        let parser = Parser::new();
+        let site = parser
+            .parse_file("tests/simplewiki-history.xml")
+            .expect("Could not parse simplewiki history dump");
+
+        let json = serde_json::to_string(&site).expect("Could not serialize site");
+        let cached: Site = serde_json::from_str(&json).expect("Could not deserialize site");
+
+        assert_eq!(cached.name, site.name);
+        assert_eq!(cached.dbname, site.dbname);
+        assert_eq!(cached.url, site.url);
+        assert_eq!(cached.pages.len(), site.pages.len());
+
+        let page = &cached.pages[0];
+
+        assert_eq!(page.title, "Moon");
+        assert_eq!(page.id, 2412);
+        assert_eq!(page.revisions.len(), 4);
+
+        for (cached, revision) in page.revisions.iter().zip(&site.pages[0].revisions) {
+            assert_eq!(cached.id, revision.id);
+            assert_eq!(cached.parent_id, revision.parent_id);
+            assert_eq!(cached.contributor, revision.contributor);
+            assert_eq!(cached.comment, revision.comment);
+            assert_eq!(cached.text_content, revision.text_content);
+            assert_eq!(cached.text, revision.text);
+            assert_eq!(cached.raw, revision.raw);
+        }
+
+        assert_eq!(page.revisions[2].contributor, Contributor::Deleted);
+        assert_eq!(page.revisions[2].comment, Comment::Deleted);
+        assert_eq!(page.revisions[2].text_content, TextContent::Deleted);
+    }
+
+    #[test]
+    fn can_serialize_revision_slots() {
+        let parser = Parser::new().exclude_pages(false);
+        let site = parser
+            .parse_file("tests/commonswiki-0.11.xml")
+            .expect("Could not parse commonswiki dump");
+
+        let json = serde_json::to_string(&site.pages[1]).expect("Could not serialize page");
+        let cached: wikidump::Page =
+            serde_json::from_str(&json).expect("Could not deserialize page");
+
+        let slot = cached.revisions[0]
+            .slot("mediainfo")
+            .expect("Could not get slot");
+
+        assert_eq!(slot.origin, Some(790554126));
+        assert_eq!(slot.model, "wikibase-mediainfo");
+    }
+}